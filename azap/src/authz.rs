@@ -0,0 +1,136 @@
+//! Declarative authorization with `#[requires(role = "...", scope = "...")]`.
+//!
+//! An `authenticate` guard returning `Authenticated<Principal>` provides the caller, as does
+//! a guard registered with `provides = Principal`, through `#[register_guard]` or its
+//! `register_guards!` entry, that stores it with
+//! `req.extensions_mut().insert(Guarded(principal))`. Routes with a `#[requires(...)]`
+//! but no such guard are rejected at build time. `azap_codegen` then wraps
+//! every route with a `#[requires(...)]` in [`enforce`], which answers `401` without a
+//! principal and `403` when a required role or scope is missing. The same requirements
+//! are recorded in the route's [`RouteMetaData`](crate::RouteMetaData).
+//!
+//! ```no_run
+//! use azap::{authz::Principal, get, guards, requires, Guarded};
+//!
+//! #[get("/{id}")]
+//! #[guards(auth)]
+//! #[requires(role = "admin", scope = "users:write")]
+//! pub async fn delete_user(Guarded(principal): Guarded<Principal>) {}
+//! ```
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{Error, Guarded};
+
+/// The authenticated caller, as attached by an auth guard.
+#[derive(Debug, Clone, Default)]
+pub struct Principal {
+    pub roles: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+impl Principal {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn role(mut self, role: impl Into<String>) -> Self {
+        self.roles.push(role.into());
+        self
+    }
+
+    pub fn scope(mut self, scope: impl Into<String>) -> Self {
+        self.scopes.push(scope.into());
+        self
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r == role)
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.iter().any(|s| s == scope)
+    }
+}
+
+/// The roles and scopes a route requires. The principal needs all of them.
+#[derive(Debug, Clone)]
+pub struct Requirements(Arc<Inner>);
+
+#[derive(Debug)]
+struct Inner {
+    roles: &'static [&'static str],
+    scopes: &'static [&'static str],
+}
+
+impl Requirements {
+    pub fn new(roles: &'static [&'static str], scopes: &'static [&'static str]) -> Self {
+        Self(Arc::new(Inner { roles, scopes }))
+    }
+
+    /// Returns the first role or scope `principal` lacks, as `("role", name)` or
+    /// `("scope", name)`.
+    pub fn missing(&self, principal: &Principal) -> Option<(&'static str, &'static str)> {
+        let role = self
+            .0
+            .roles
+            .iter()
+            .find(|role| !principal.has_role(role))
+            .map(|role| ("role", *role));
+
+        role.or_else(|| {
+            self.0
+                .scopes
+                .iter()
+                .find(|scope| !principal.has_scope(scope))
+                .map(|scope| ("scope", *scope))
+        })
+    }
+}
+
+/// Middleware rejecting requests whose principal doesn't meet the route's requirements.
+pub async fn enforce(
+    State(requirements): State<Requirements>,
+    req: Request,
+    next: Next,
+) -> Response {
+    let Some(Guarded(principal)) = req.extensions().get::<Guarded<Principal>>() else {
+        return Error::new(StatusCode::UNAUTHORIZED).into_response();
+    };
+
+    if let Some((kind, name)) = requirements.missing(principal) {
+        return Error::new(StatusCode::FORBIDDEN)
+            .detail(format!("Missing {} `{}`", kind, name))
+            .into_response();
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing() {
+        let requirements = Requirements::new(&["admin"], &["users:read", "users:write"]);
+        let principal = Principal::new().role("admin").scope("users:read");
+
+        assert_eq!(
+            requirements.missing(&principal),
+            Some(("scope", "users:write"))
+        );
+        assert_eq!(
+            requirements.missing(&Principal::new().scope("users:write")),
+            Some(("role", "admin"))
+        );
+        assert_eq!(requirements.missing(&principal.scope("users:write")), None);
+    }
+}
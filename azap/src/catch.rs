@@ -0,0 +1,90 @@
+//! Response mapping for `#[catch(status)]` handlers.
+//!
+//! `azap_codegen` collects the catchers of a route root into [`Catchers`] and layers
+//! [`catch`] over the whole generated router, including fallbacks.
+
+use std::{future::Future, pin::Pin, sync::Arc};
+
+use axum::{
+    body::HttpBody,
+    extract::{Request, State},
+    http::{HeaderMap, Method, StatusCode, Uri},
+    middleware::Next,
+    response::Response,
+};
+
+use crate::Error;
+
+/// The boxed handler generated next to a `#[catch]` function.
+pub type Catcher = fn(Caught) -> Pin<Box<dyn Future<Output = Response> + Send>>;
+
+/// The request whose response is being replaced.
+#[derive(Debug, Clone)]
+pub struct Caught {
+    pub status: StatusCode,
+    pub method: Method,
+    pub uri: Uri,
+    pub headers: HeaderMap,
+}
+
+impl Caught {
+    /// A problem details [`Error`] for the status, with the request path as `instance`.
+    pub fn problem(&self) -> Error {
+        Error::new(self.status).instance(self.uri.path())
+    }
+}
+
+/// Catchers by status code.
+#[derive(Clone, Default)]
+pub struct Catchers(Arc<Vec<(StatusCode, Catcher)>>);
+
+impl Catchers {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Registers `catcher` for `status`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `status` isn't a valid status code. `azap_codegen` checks it at build time.
+    pub fn on(mut self, status: u16, catcher: Catcher) -> Self {
+        let status = StatusCode::from_u16(status).expect("catcher status is valid");
+        Arc::make_mut(&mut self.0).push((status, catcher));
+        self
+    }
+
+    fn get(&self, status: StatusCode) -> Option<Catcher> {
+        self.0
+            .iter()
+            .find(|(caught, _)| *caught == status)
+            .map(|(_, catcher)| *catcher)
+    }
+}
+
+/// Replaces responses that have a catcher for their status and an empty body, such as
+/// axum's default 404 or a guard's bare `StatusCode`. Responses with a body, like an
+/// [`Error`], are left alone.
+pub async fn catch(State(catchers): State<Catchers>, req: Request, next: Next) -> Response {
+    let method = req.method().clone();
+    let uri = req.uri().clone();
+    let headers = req.headers().clone();
+
+    let response = next.run(req).await;
+
+    let Some(catcher) = catchers.get(response.status()) else {
+        return response;
+    };
+
+    if response.body().size_hint().exact() != Some(0) {
+        return response;
+    }
+
+    catcher(Caught {
+        status: response.status(),
+        method,
+        uri,
+        headers,
+    })
+    .await
+}
@@ -0,0 +1,114 @@
+//! Runtime checks for `{param:constraint}` route segments.
+//!
+//! `azap_codegen` strips constraints from route paths before handing them to axum and
+//! wraps the route in [`enforce`], so a request whose segment doesn't satisfy the
+//! constraint gets a `404 Not Found` instead of reaching the handler.
+//!
+//! Type constraints answer `404` too, not `400`. A segment that doesn't satisfy its
+//! constraint means the path doesn't name the route, like a literal segment that doesn't
+//! match, so `/users/me` against `/users/{id:u64}` reads as an unknown path rather than
+//! a malformed id. Handlers that want a `400` take the param unconstrained and let
+//! `Path<u64>` reject it.
+
+use std::sync::Arc;
+
+use axum::{
+    extract::{RawPathParams, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+use regex::Regex;
+
+#[derive(Debug, Clone)]
+enum Constraint {
+    Type(&'static str),
+    Regex(Regex),
+}
+
+impl Constraint {
+    fn is_match(&self, value: &str) -> bool {
+        match self {
+            Self::Type(ty) => azap_syntax::parses_as(ty, value).unwrap_or(false),
+            Self::Regex(regex) => regex.is_match(value),
+        }
+    }
+}
+
+/// The constraints of a single route, keyed by param name.
+#[derive(Debug, Clone)]
+pub struct PathConstraints(Arc<[(&'static str, Constraint)]>);
+
+impl PathConstraints {
+    /// Builds the constraints from `(param, constraint)` pairs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a regex constraint is invalid. `azap_codegen` validates them at build time.
+    pub fn new(constraints: &[(&'static str, &'static str)]) -> Self {
+        let constraints = constraints
+            .iter()
+            .map(|&(name, constraint)| {
+                let is_type = constraint
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+                let constraint = if is_type {
+                    Constraint::Type(constraint)
+                } else {
+                    // Constraints must match the whole segment.
+                    let regex = Regex::new(&format!("^(?:{})$", constraint))
+                        .unwrap_or_else(|err| panic!("invalid constraint for `{}`: {}", name, err));
+                    Constraint::Regex(regex)
+                };
+
+                (name, constraint)
+            })
+            .collect();
+
+        Self(constraints)
+    }
+
+    /// Returns `true` if `value` satisfies the constraint on `param`, or if there is none.
+    pub fn is_match(&self, param: &str, value: &str) -> bool {
+        self.0
+            .iter()
+            .filter(|(name, _)| *name == param)
+            .all(|(_, constraint)| constraint.is_match(value))
+    }
+}
+
+/// Middleware rejecting requests whose path params don't satisfy their constraints.
+pub async fn enforce(
+    State(constraints): State<PathConstraints>,
+    params: RawPathParams,
+    req: Request,
+    next: Next,
+) -> Response {
+    if params
+        .iter()
+        .any(|(name, value)| !constraints.is_match(name, value))
+    {
+        return StatusCode::NOT_FOUND.into_response();
+    }
+
+    next.run(req).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_match() {
+        let constraints = PathConstraints::new(&[("id", "u64"), ("slug", "[a-z-]+")]);
+
+        assert!(constraints.is_match("id", "42"));
+        assert!(!constraints.is_match("id", "me"));
+        assert!(!constraints.is_match("id", "-1"));
+        assert!(constraints.is_match("slug", "hello-world"));
+        assert!(!constraints.is_match("slug", "Hello"));
+        assert!(!constraints.is_match("slug", "hello/world"));
+        assert!(constraints.is_match("other", "anything"));
+    }
+}
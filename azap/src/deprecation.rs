@@ -0,0 +1,56 @@
+//! Deprecation headers for version directories marked with
+//! `azap::routes!(deprecated = ..., sunset = ..., link = ...)`.
+
+use axum::{
+    extract::{Request, State},
+    http::{header::LINK, HeaderName, HeaderValue},
+    middleware::Next,
+    response::Response,
+};
+
+static DEPRECATION: HeaderName = HeaderName::from_static("deprecation");
+static SUNSET: HeaderName = HeaderName::from_static("sunset");
+
+/// Header values added to every response of a deprecated version.
+#[derive(Debug, Clone)]
+pub struct Deprecation {
+    deprecation: Option<HeaderValue>,
+    sunset: Option<HeaderValue>,
+    link: Option<HeaderValue>,
+}
+
+impl Deprecation {
+    /// `azap_codegen` validates the values at build time, so they're always valid headers.
+    pub fn new(
+        deprecation: Option<&'static str>,
+        sunset: Option<&'static str>,
+        link: Option<&'static str>,
+    ) -> Self {
+        Self {
+            deprecation: deprecation.map(HeaderValue::from_static),
+            sunset: sunset.map(HeaderValue::from_static),
+            link: link.map(|url| {
+                HeaderValue::from_str(&format!("<{}>; rel=\"deprecation\"", url))
+                    .expect("link is a valid header value")
+            }),
+        }
+    }
+}
+
+/// Adds the `Deprecation`, `Sunset` and `Link` headers to the response.
+pub async fn headers(State(deprecation): State<Deprecation>, req: Request, next: Next) -> Response {
+    let mut response = next.run(req).await;
+    let headers = response.headers_mut();
+
+    if let Some(value) = deprecation.deprecation {
+        headers.insert(DEPRECATION.clone(), value);
+    }
+    if let Some(value) = deprecation.sunset {
+        headers.insert(SUNSET.clone(), value);
+    }
+    if let Some(value) = deprecation.link {
+        headers.append(LINK, value);
+    }
+
+    response
+}
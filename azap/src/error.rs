@@ -0,0 +1,192 @@
+//! A handler error rendered as an RFC 9457 `application/problem+json` body.
+
+use std::fmt;
+
+use axum::{
+    extract::rejection::{FormRejection, JsonRejection, PathRejection, QueryRejection},
+    http::{header, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde_json::{Map, Value};
+
+/// Content type of [`Error`] responses.
+pub const PROBLEM_JSON: &str = "application/problem+json";
+
+/// `Result` with [`Error`] as the error type.
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+/// An RFC 9457 problem details response.
+///
+/// `title` defaults to the status' reason phrase and `type` is left out, which RFC 9457
+/// reads as `about:blank`. Extra members go through [`Error::with`].
+///
+/// # Examples
+///
+/// ```no_run
+/// use azap::{Error, StatusCode};
+///
+/// async fn get_user() -> azap::Result<String> {
+///     Err(Error::new(StatusCode::NOT_FOUND)
+///         .detail("User 42 doesn't exist")
+///         .with("user_id", 42))
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Error {
+    status: StatusCode,
+    members: Map<String, Value>,
+}
+
+impl Error {
+    pub fn new(status: StatusCode) -> Self {
+        let mut members = Map::new();
+        members.insert(
+            "title".to_string(),
+            status.canonical_reason().unwrap_or("Unknown Error").into(),
+        );
+        members.insert("status".to_string(), status.as_u16().into());
+
+        Self { status, members }
+    }
+
+    pub fn bad_request(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::BAD_REQUEST).detail(detail)
+    }
+
+    pub fn not_found(detail: impl Into<String>) -> Self {
+        Self::new(StatusCode::NOT_FOUND).detail(detail)
+    }
+
+    pub fn internal() -> Self {
+        Self::new(StatusCode::INTERNAL_SERVER_ERROR)
+    }
+
+    /// A URI identifying the problem type.
+    pub fn type_uri(self, uri: impl Into<String>) -> Self {
+        self.with("type", uri.into())
+    }
+
+    /// A short summary of the problem type, replacing the reason phrase.
+    pub fn title(self, title: impl Into<String>) -> Self {
+        self.with("title", title.into())
+    }
+
+    /// An explanation specific to this occurrence of the problem.
+    pub fn detail(self, detail: impl Into<String>) -> Self {
+        self.with("detail", detail.into())
+    }
+
+    /// A URI identifying this occurrence, e.g. the request path.
+    pub fn instance(self, instance: impl Into<String>) -> Self {
+        self.with("instance", instance.into())
+    }
+
+    /// Adds an extension member. `status` can't be overridden.
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        if key != "status" {
+            self.members.insert(key.to_string(), value.into());
+        }
+        self
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    /// Returns a member of the problem body, e.g. `"detail"`.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.members.get(key)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.status)?;
+        if let Some(Value::String(detail)) = self.members.get("detail") {
+            write!(f, ": {}", detail)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl IntoResponse for Error {
+    fn into_response(self) -> Response {
+        let body = Value::Object(self.members).to_string();
+
+        (
+            self.status,
+            [(header::CONTENT_TYPE, HeaderValue::from_static(PROBLEM_JSON))],
+            body,
+        )
+            .into_response()
+    }
+}
+
+impl From<StatusCode> for Error {
+    fn from(status: StatusCode) -> Self {
+        Self::new(status)
+    }
+}
+
+/// A `500 Internal Server Error`. The error chain is only sent as `detail` in debug
+/// builds, so internals don't leak in production.
+impl From<anyhow::Error> for Error {
+    fn from(err: anyhow::Error) -> Self {
+        match cfg!(debug_assertions) {
+            true => Self::internal().detail(format!("{:#}", err)),
+            false => Self::internal(),
+        }
+    }
+}
+
+/// Malformed JSON is the client's fault (`400`), failing to write it is ours (`500`).
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        match err.classify() {
+            serde_json::error::Category::Io => Self::internal(),
+            _ => Self::bad_request(err.to_string()),
+        }
+    }
+}
+
+macro_rules! from_rejection {
+    ($($rejection:ty),*) => {
+        $(
+            impl From<$rejection> for Error {
+                fn from(rejection: $rejection) -> Self {
+                    Self::new(rejection.status()).detail(rejection.body_text())
+                }
+            }
+        )*
+    };
+}
+
+from_rejection!(JsonRejection, PathRejection, QueryRejection, FormRejection);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_problem_body() {
+        let error = Error::not_found("no user 42")
+            .instance("/users/42")
+            .with("status", 200)
+            .with("user_id", 42);
+
+        assert_eq!(error.status(), StatusCode::NOT_FOUND);
+        assert_eq!(error.to_string(), "404 Not Found: no user 42");
+
+        let response = error.into_response();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers()[header::CONTENT_TYPE], PROBLEM_JSON);
+    }
+
+    #[test]
+    fn test_from_serde_json() {
+        let err = serde_json::from_str::<u32>("nope").unwrap_err();
+        assert_eq!(Error::from(err).status(), StatusCode::BAD_REQUEST);
+    }
+}
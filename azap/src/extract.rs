@@ -0,0 +1,483 @@
+//! `Json`, `Path` and `Query` extractors whose rejections carry the failing field and
+//! render as problem details, or through the app's `#[rejection]` handler.
+//!
+//! They are drop-in replacements for the axum extractors of the same name and are what
+//! `azap::{Json, Path, Query}` and the prelude export.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use axum::{
+    body::Bytes,
+    extract::{
+        rejection::PathRejection, FromRequest, FromRequestParts, OptionalFromRequestParts, Request,
+    },
+    http::{header, request::Parts, HeaderMap, StatusCode},
+    response::{IntoResponse, Response},
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::{
+    validate::{FieldError, ValidationErrors},
+    Error,
+};
+
+/// A function rendering every [`Rejection`] of a router, registered with `#[rejection]`.
+pub type RejectionHandler = fn(Rejection) -> Response;
+
+/// Request extension through which `azap_codegen` hands the `#[rejection]` handler to
+/// the extractors.
+#[derive(Clone, Copy)]
+pub struct Rejections(pub RejectionHandler);
+
+/// The extractor that rejected a request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Json,
+    Path,
+    Query,
+    /// A [`Validate`](crate::Validate) implementation, run by [`Valid`](crate::Valid).
+    Validation,
+}
+
+impl Source {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Json => "body",
+            Self::Path => "path",
+            Self::Query => "query",
+            Self::Validation => "validation",
+        }
+    }
+}
+
+/// Why a [`Json`], [`Path`] or [`Query`] extractor failed, or why
+/// [`Valid`](crate::Valid) rejected what they extracted.
+pub struct Rejection {
+    source: Source,
+    status: StatusCode,
+    message: String,
+    field: Option<String>,
+    errors: Vec<FieldError>,
+    handler: Option<RejectionHandler>,
+}
+
+impl Rejection {
+    fn new(source: Source, status: StatusCode, message: impl Into<String>) -> Self {
+        Self {
+            source,
+            status,
+            message: message.into(),
+            field: None,
+            errors: Vec::new(),
+            handler: None,
+        }
+    }
+
+    pub(crate) fn validation(errors: ValidationErrors) -> Self {
+        Self {
+            errors: errors.into_iter().collect(),
+            ..Self::new(
+                Source::Validation,
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "Request validation failed",
+            )
+        }
+    }
+
+    fn field(mut self, field: impl Into<String>) -> Self {
+        let field = field.into();
+        // `.` is what serde_path_to_error reports for the value itself
+        if !field.is_empty() && field != "." {
+            self.field = Some(field);
+        }
+        self
+    }
+
+    pub(crate) fn handled_by(self, extensions: &axum::http::Extensions) -> Self {
+        self.with_handler(extensions.get::<Rejections>().copied())
+    }
+
+    pub(crate) fn with_handler(mut self, rejections: Option<Rejections>) -> Self {
+        self.handler = rejections.map(|rejections| rejections.0);
+        self
+    }
+
+    pub fn source(&self) -> Source {
+        self.source
+    }
+
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Path to the offending field, e.g. `address.zip` or `items[2].id`.
+    pub fn field_path(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    /// Every failing field of a [`Source::Validation`] rejection.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    /// The default rendering: a problem details [`Error`] with the `source` and `field`
+    /// of the failure as extension members, plus `errors` for validation failures.
+    pub fn into_error(self) -> Error {
+        let mut error = Error::new(self.status)
+            .detail(self.message)
+            .with("source", self.source.as_str());
+
+        if let Some(field) = self.field {
+            error = error.with("field", field);
+        }
+
+        if !self.errors.is_empty() {
+            let errors: Vec<_> = self
+                .errors
+                .into_iter()
+                .map(|error| serde_json::json!({ "field": error.field, "message": error.message }))
+                .collect();
+            error = error.with("errors", errors);
+        }
+
+        error
+    }
+}
+
+impl fmt::Debug for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Rejection")
+            .field("source", &self.source)
+            .field("status", &self.status)
+            .field("message", &self.message)
+            .field("field", &self.field)
+            .field("errors", &self.errors)
+            .finish()
+    }
+}
+
+impl fmt::Display for Rejection {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.field {
+            Some(field) => write!(f, "{} at `{}`", self.message, field),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for Rejection {}
+
+impl IntoResponse for Rejection {
+    fn into_response(self) -> Response {
+        match self.handler {
+            Some(handler) => handler(self),
+            None => self.into_error().into_response(),
+        }
+    }
+}
+
+impl From<Rejection> for Error {
+    fn from(rejection: Rejection) -> Self {
+        rejection.into_error()
+    }
+}
+
+macro_rules! wrapper {
+    ($name:ident) => {
+        impl<T> Deref for $name<T> {
+            type Target = T;
+
+            fn deref(&self) -> &T {
+                &self.0
+            }
+        }
+
+        impl<T> DerefMut for $name<T> {
+            fn deref_mut(&mut self) -> &mut T {
+                &mut self.0
+            }
+        }
+
+        impl<T> From<T> for $name<T> {
+            fn from(value: T) -> Self {
+                Self(value)
+            }
+        }
+    };
+}
+
+/// A JSON request body, or a JSON response.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Json<T>(pub T);
+
+wrapper!(Json);
+
+impl<T, S> FromRequest<S> for Json<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let handler = req.extensions().get::<Rejections>().copied();
+        let reject = |rejection: Rejection| rejection.with_handler(handler);
+
+        if !is_json(req.headers()) {
+            return Err(reject(Rejection::new(
+                Source::Json,
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "Expected request with `Content-Type: application/json`",
+            )));
+        }
+
+        let bytes = Bytes::from_request(req, state)
+            .await
+            .map_err(|err| reject(Rejection::new(Source::Json, err.status(), err.body_text())))?;
+
+        let deserializer = &mut serde_json::Deserializer::from_slice(&bytes);
+
+        serde_path_to_error::deserialize(deserializer)
+            .map(Json)
+            .map_err(|err| {
+                let status = match err.inner().classify() {
+                    serde_json::error::Category::Data => StatusCode::UNPROCESSABLE_ENTITY,
+                    _ => StatusCode::BAD_REQUEST,
+                };
+                let field = err.path().to_string();
+
+                reject(
+                    Rejection::new(Source::Json, status, err.into_inner().to_string()).field(field),
+                )
+            })
+    }
+}
+
+impl<T: Serialize> IntoResponse for Json<T> {
+    fn into_response(self) -> Response {
+        axum::Json(self.0).into_response()
+    }
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    let Some(content_type) = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+    else {
+        return false;
+    };
+
+    let mime = content_type
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim()
+        .to_ascii_lowercase();
+
+    mime == "application/json" || (mime.starts_with("application/") && mime.ends_with("+json"))
+}
+
+/// Path parameters, deserialized like `axum::extract::Path`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Path<T>(pub T);
+
+wrapper!(Path);
+
+impl<T, S> FromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send,
+    S: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        match <axum::extract::Path<T> as FromRequestParts<S>>::from_request_parts(parts, state)
+            .await
+        {
+            Ok(axum::extract::Path(value)) => Ok(Path(value)),
+            Err(rejection) => Err(path_rejection(rejection).handled_by(&parts.extensions)),
+        }
+    }
+}
+
+/// `None` on routes without path parameters, such as the expansion of `/{lang?}/docs`
+/// that leaves `lang` out.
+impl<T, S> OptionalFromRequestParts<S> for Path<T>
+where
+    T: DeserializeOwned + Send + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &S,
+    ) -> Result<Option<Self>, Self::Rejection> {
+        match <axum::extract::Path<T> as OptionalFromRequestParts<S>>::from_request_parts(
+            parts, state,
+        )
+        .await
+        {
+            Ok(value) => Ok(value.map(|axum::extract::Path(value)| Path(value))),
+            Err(rejection) => Err(path_rejection(rejection).handled_by(&parts.extensions)),
+        }
+    }
+}
+
+fn path_rejection(rejection: PathRejection) -> Rejection {
+    use axum::extract::path::ErrorKind;
+
+    let status = rejection.status();
+    let PathRejection::FailedToDeserializePathParams(err) = rejection else {
+        return Rejection::new(Source::Path, status, rejection.body_text());
+    };
+
+    let field = match err.kind() {
+        ErrorKind::ParseErrorAtKey { key, .. }
+        | ErrorKind::DeserializeError { key, .. }
+        | ErrorKind::InvalidUtf8InPathParam { key } => key.clone(),
+        ErrorKind::ParseErrorAtIndex { index, .. } => index.to_string(),
+        _ => String::new(),
+    };
+
+    Rejection::new(Source::Path, status, err.kind().to_string()).field(field)
+}
+
+/// A query string, deserialized like `axum::extract::Query`.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Query<T>(pub T);
+
+wrapper!(Query);
+
+impl<T, S> FromRequestParts<S> for Query<T>
+where
+    T: DeserializeOwned,
+    S: Send + Sync,
+{
+    type Rejection = Rejection;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        let query = parts.uri.query().unwrap_or_default();
+        let deserializer =
+            serde_urlencoded::Deserializer::new(form_urlencoded::parse(query.as_bytes()));
+
+        serde_path_to_error::deserialize(deserializer)
+            .map(Query)
+            .map_err(|err| {
+                let field = err.path().to_string();
+
+                Rejection::new(
+                    Source::Query,
+                    StatusCode::BAD_REQUEST,
+                    err.into_inner().to_string(),
+                )
+                .field(field)
+                .handled_by(&parts.extensions)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Order {
+        items: Vec<Item>,
+    }
+
+    #[derive(Debug, Deserialize)]
+    #[allow(dead_code)]
+    struct Item {
+        id: u32,
+    }
+
+    fn json_request(body: &'static str) -> Request {
+        Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_json_rejection_field() {
+        let rejection =
+            Json::<Order>::from_request(json_request(r#"{"items":[{"id":1},{"id":"x"}]}"#), &())
+                .await
+                .unwrap_err();
+
+        assert_eq!(rejection.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(rejection.field_path(), Some("items[1].id"));
+        assert_eq!(rejection.into_error().get("source").unwrap(), "body");
+
+        let rejection = Json::<Order>::from_request(json_request("{"), &())
+            .await
+            .unwrap_err();
+        assert_eq!(rejection.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_optional_path() {
+        use crate::testing::TestClient;
+        use axum::{routing::get, Router};
+
+        async fn docs(lang: Option<Path<String>>) -> String {
+            lang.map_or_else(|| "en".to_string(), |Path(lang)| lang)
+        }
+
+        let client = TestClient::new(
+            Router::new()
+                .route("/{lang}/docs", get(docs))
+                .route("/docs", get(docs))
+                .route(
+                    "/{id}",
+                    get(|id: Option<Path<u32>>| async move { id.is_some().to_string() }),
+                ),
+        );
+
+        assert_eq!(client.get("/fr/docs").send().await.text(), "fr");
+        assert_eq!(client.get("/docs").send().await.text(), "en");
+        client
+            .get("/x")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_rejection_handler() {
+        fn teapot(_: Rejection) -> Response {
+            StatusCode::IM_A_TEAPOT.into_response()
+        }
+
+        let mut req = Request::builder()
+            .uri("/?page=two")
+            .body(Body::empty())
+            .unwrap();
+        req.extensions_mut().insert(Rejections(teapot));
+        let (mut parts, _) = req.into_parts();
+
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Page {
+            page: u32,
+        }
+
+        let rejection = Query::<Page>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+
+        assert_eq!(rejection.field_path(), Some("page"));
+        assert_eq!(rejection.into_response().status(), StatusCode::IM_A_TEAPOT);
+    }
+}
@@ -0,0 +1,202 @@
+//! Trait-based guards: structs that carry their own configuration.
+//!
+//! ```
+//! use azap::{
+//!     axum::http::request::Parts,
+//!     guard::Guard,
+//!     register_guard, IntoResponse, Response, StatusCode,
+//! };
+//!
+//! pub struct RequireHeader(&'static str);
+//!
+//! impl<S: Sync> Guard<S> for RequireHeader {
+//!     async fn check(&self, parts: &mut Parts, _state: &S) -> Result<(), Response> {
+//!         match parts.headers.contains_key(self.0) {
+//!             true => Ok(()),
+//!             false => Err(StatusCode::BAD_REQUEST.into_response()),
+//!         }
+//!     }
+//! }
+//!
+//! // In src/guards/headers.rs, used as #[guards(headers::API_KEY)]
+//! #[register_guard]
+//! pub const API_KEY: RequireHeader = RequireHeader("x-api-key");
+//! ```
+//!
+//! `#[register_guard]` on the `impl Guard<S> for T` block instead builds the guard from
+//! the router state with `FromRef<S>`, for guards configured at startup.
+
+use std::{
+    convert::Infallible,
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+use axum::{
+    extract::{FromRef, Request},
+    http::request::Parts,
+    response::Response,
+};
+use tower::{Layer, Service};
+
+/// A guard checking the request before the handler runs.
+pub trait Guard<S>: Send + Sync + 'static {
+    /// Returns `Err` with the response to send instead of running the handler.
+    fn check(
+        &self,
+        parts: &mut Parts,
+        state: &S,
+    ) -> impl Future<Output = Result<(), Response>> + Send;
+}
+
+/// Wraps a guard instance, e.g. a `#[register_guard]` const, in a layer.
+pub fn layer<G, S>(guard: G, state: S) -> GuardLayer<G, S> {
+    GuardLayer {
+        guard: Arc::new(guard),
+        state,
+    }
+}
+
+/// Builds a guard from the router state, for an `impl Guard<S>` registered with
+/// `#[register_guard]`.
+pub fn from_state<G, S>(state: S) -> GuardLayer<G, S>
+where
+    G: FromRef<S>,
+{
+    layer(G::from_ref(&state), state)
+}
+
+/// A [`Layer`] running a [`Guard`] in front of the wrapped service.
+pub struct GuardLayer<G, S> {
+    guard: Arc<G>,
+    state: S,
+}
+
+impl<G, S: Clone> Clone for GuardLayer<G, S> {
+    fn clone(&self) -> Self {
+        Self {
+            guard: self.guard.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<G, S: Clone, I> Layer<I> for GuardLayer<G, S> {
+    type Service = GuardService<G, S, I>;
+
+    fn layer(&self, inner: I) -> Self::Service {
+        GuardService {
+            guard: self.guard.clone(),
+            state: self.state.clone(),
+            inner,
+        }
+    }
+}
+
+/// The [`Service`] created by [`GuardLayer`].
+pub struct GuardService<G, S, I> {
+    guard: Arc<G>,
+    state: S,
+    inner: I,
+}
+
+impl<G, S: Clone, I: Clone> Clone for GuardService<G, S, I> {
+    fn clone(&self) -> Self {
+        Self {
+            guard: self.guard.clone(),
+            state: self.state.clone(),
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<G, S, I> Service<Request> for GuardService<G, S, I>
+where
+    G: Guard<S>,
+    S: Clone + Send + Sync + 'static,
+    I: Service<Request, Response = Response, Error = Infallible> + Clone + Send + 'static,
+    I::Future: Send + 'static,
+{
+    type Response = Response;
+    type Error = Infallible;
+    type Future = Pin<Box<dyn Future<Output = Result<Response, Infallible>> + Send>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, req: Request) -> Self::Future {
+        let guard = self.guard.clone();
+        let state = self.state.clone();
+        // The clone may not be ready, so call the instance `poll_ready` was called on
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+
+        Box::pin(async move {
+            let (mut parts, body) = req.into_parts();
+
+            match guard.check(&mut parts, &state).await {
+                Ok(()) => inner.call(Request::from_parts(parts, body)).await,
+                Err(response) => Ok(response),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestClient;
+    use axum::{http::StatusCode, response::IntoResponse, routing::get, Router};
+
+    struct MinVersion(u32);
+
+    impl Guard<AppState> for MinVersion {
+        async fn check(&self, parts: &mut Parts, state: &AppState) -> Result<(), Response> {
+            let version = parts
+                .headers
+                .get("x-version")
+                .and_then(|value| value.to_str().ok()?.parse::<u32>().ok())
+                .unwrap_or_default();
+
+            match version >= self.0.max(state.min_version) {
+                true => Ok(()),
+                false => Err(StatusCode::UPGRADE_REQUIRED.into_response()),
+            }
+        }
+    }
+
+    #[derive(Clone)]
+    struct AppState {
+        min_version: u32,
+    }
+
+    impl FromRef<AppState> for MinVersion {
+        fn from_ref(state: &AppState) -> Self {
+            MinVersion(state.min_version)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_guard_layer() {
+        let state = AppState { min_version: 2 };
+        let router = Router::new()
+            .route("/", get(|| async { "ok" }))
+            .layer(from_state::<MinVersion, _>(state.clone()))
+            .with_state(state);
+        let client = TestClient::new(router);
+
+        client
+            .get("/")
+            .header("x-version", "1")
+            .send()
+            .await
+            .assert_status(StatusCode::UPGRADE_REQUIRED);
+        assert_eq!(
+            client.get("/").header("x-version", "3").send().await.text(),
+            "ok"
+        );
+    }
+}
@@ -0,0 +1,233 @@
+//! Values attached to the request by guards, e.g. the user an `auth` guard
+//! authenticated.
+//!
+//! An `authenticate` guard takes extractors and returns the value, which azap stores for
+//! handlers to take as `Guarded<User>`:
+//!
+//! ```
+//! use azap::{
+//!     axum::http::HeaderMap,
+//!     guarded::Authenticated,
+//!     register_guard, StatusCode,
+//! };
+//!
+//! #[derive(Clone)]
+//! pub struct User(String);
+//!
+//! #[register_guard(guard_type = "authenticate")]
+//! pub async fn auth(headers: HeaderMap) -> Result<Authenticated<User>, StatusCode> {
+//!     let user = headers.get("x-user").and_then(|value| value.to_str().ok());
+//!     match user {
+//!         Some(user) => Ok(Authenticated(User(user.to_string()))),
+//!         None => Err(StatusCode::UNAUTHORIZED),
+//!     }
+//! }
+//! ```
+//!
+//! Middleware guards registered with
+//! `#[register_guard(guard_type = "...", provides = User)]` store the value themselves with
+//! `req.extensions_mut().insert(Guarded(user))`. `azap_codegen` fails the build when a
+//! handler extracts a `Guarded<T>` that none of its route's guards provides.
+
+use std::{
+    any::type_name,
+    convert::Infallible,
+    future::Future,
+    marker::PhantomData,
+    ops::{Deref, DerefMut},
+};
+
+use axum::{
+    extract::FromRequestParts,
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+
+use crate::{guard::Guard, Error};
+
+/// A value a guard attached to the request, and the extractor reading it back.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Guarded<T>(pub T);
+
+impl<T> Deref for Guarded<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T> DerefMut for Guarded<T> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.0
+    }
+}
+
+impl<T, S> FromRequestParts<S> for Guarded<T>
+where
+    T: Clone + Send + Sync + 'static,
+    S: Send + Sync,
+{
+    type Rejection = Error;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Guarded<T>>()
+            .cloned()
+            .ok_or_else(|| {
+                // Only reachable when codegen couldn't check the route, e.g. the value
+                // comes from an external guard that didn't attach it.
+                let error = Error::internal();
+                match cfg!(debug_assertions) {
+                    true => error.detail(format!("No guard provided `{}`", type_name::<T>())),
+                    false => error,
+                }
+            })
+    }
+}
+
+/// The value an `authenticate` guard returns, stored as [`Guarded<T>`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Authenticated<T>(pub T);
+
+/// What an `authenticate` guard may return: `Authenticated<T>`, or a `Result` whose error
+/// is sent instead of running the handler.
+pub trait IntoAuthenticated {
+    type Value;
+    type Rejection: IntoResponse;
+
+    fn into_authenticated(self) -> Result<Self::Value, Self::Rejection>;
+}
+
+impl<T> IntoAuthenticated for Authenticated<T> {
+    type Value = T;
+    type Rejection = Infallible;
+
+    fn into_authenticated(self) -> Result<T, Infallible> {
+        Ok(self.0)
+    }
+}
+
+impl<T, E: IntoResponse> IntoAuthenticated for Result<Authenticated<T>, E> {
+    type Value = T;
+    type Rejection = E;
+
+    fn into_authenticated(self) -> Result<T, E> {
+        self.map(|Authenticated(value)| value)
+    }
+}
+
+/// Runs an `authenticate` guard function as a [`Guard`], for `azap::guard::layer`.
+///
+/// `Args` are the extractors the function takes, all of them `FromRequestParts`.
+pub fn authenticate<F, Args>(f: F) -> Authenticate<F, Args> {
+    Authenticate {
+        f,
+        _args: PhantomData,
+    }
+}
+
+/// The [`Guard`] created by [`authenticate`].
+pub struct Authenticate<F, Args> {
+    f: F,
+    _args: PhantomData<fn() -> Args>,
+}
+
+macro_rules! impl_authenticate {
+    ($($extractor:ident),*) => {
+        impl<S, F, Fut, $($extractor,)*> Guard<S> for Authenticate<F, ($($extractor,)*)>
+        where
+            S: Send + Sync + 'static,
+            F: Fn($($extractor),*) -> Fut + Send + Sync + 'static,
+            Fut: Future + Send,
+            Fut::Output: IntoAuthenticated<Value: Clone + Send + Sync + 'static>,
+            $($extractor: FromRequestParts<S> + Send + 'static,)*
+        {
+            #[allow(non_snake_case, unused_variables)]
+            async fn check(&self, parts: &mut Parts, state: &S) -> Result<(), Response> {
+                $(
+                    let $extractor = $extractor::from_request_parts(parts, state)
+                        .await
+                        .map_err(IntoResponse::into_response)?;
+                )*
+
+                let value = (self.f)($($extractor),*)
+                    .await
+                    .into_authenticated()
+                    .map_err(IntoResponse::into_response)?;
+                parts.extensions.insert(Guarded(value));
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_authenticate!();
+impl_authenticate!(E1);
+impl_authenticate!(E1, E2);
+impl_authenticate!(E1, E2, E3);
+impl_authenticate!(E1, E2, E3, E4);
+impl_authenticate!(E1, E2, E3, E4, E5);
+impl_authenticate!(E1, E2, E3, E4, E5, E6);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestClient;
+    use axum::{
+        body::Body,
+        extract::Request,
+        http::{HeaderMap, StatusCode},
+        routing::get,
+        Router,
+    };
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct User(&'static str);
+
+    #[tokio::test]
+    async fn test_guarded() {
+        let mut req = Request::new(Body::empty());
+        req.extensions_mut().insert(Guarded(User("ada")));
+        let (mut parts, _) = req.into_parts();
+
+        let Guarded(user) = Guarded::<User>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        assert_eq!(user, User("ada"));
+
+        let err = Guarded::<u32>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap_err();
+        assert_eq!(err.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    async fn auth(headers: HeaderMap) -> Result<Authenticated<User>, StatusCode> {
+        match headers.contains_key("x-user") {
+            true => Ok(Authenticated(User("ada"))),
+            false => Err(StatusCode::UNAUTHORIZED),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_authenticate() {
+        let router = Router::new()
+            .route(
+                "/",
+                get(|Guarded(user): Guarded<User>| async move { user.0 }),
+            )
+            .layer(crate::guard::layer(authenticate(auth), ()));
+        let client = TestClient::new(router);
+
+        client
+            .get("/")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            client.get("/").header("x-user", "1").send().await.text(),
+            "ada"
+        );
+    }
+}
@@ -0,0 +1,9 @@
+//! Guards shipped with azap. They live in another crate, so a project lists the ones it
+//! uses in `register_guards!` in its guard directory, then names them in
+//! `#[guards(...)]` like its own guards.
+//!
+//! Each guard sits behind a feature of the same name, e.g.
+//! `azap = { version = "0.1", features = ["jwt"] }`.
+
+#[cfg(feature = "jwt")]
+pub mod jwt;
@@ -0,0 +1,346 @@
+//! JWT bearer authentication.
+//!
+//! [`authenticate`] reads `Authorization: Bearer <token>`, validates the token with the
+//! [`Jwt`] taken from the router state through `FromRef`, and stores the [`Claims`] and
+//! the [`Principal`] derived from them as `Guarded<Claims>` and `Guarded<Principal>`. A
+//! missing or invalid token gets a `401` with a `WWW-Authenticate: Bearer` header.
+//!
+//! ```no_run
+//! use azap::{axum::extract::FromRef, guards::jwt::Jwt};
+//!
+//! #[derive(Clone)]
+//! pub struct AppState {
+//!     jwt: Jwt,
+//! }
+//!
+//! impl FromRef<AppState> for Jwt {
+//!     fn from_ref(state: &AppState) -> Self {
+//!         state.jwt.clone()
+//!     }
+//! }
+//!
+//! // In src/guards/mod.rs, then used as #[guards(authenticate)]
+//! azap::register_guards! {
+//!     azap::guards::jwt::authenticate => {
+//!         guard_type = "fn_with_state",
+//!         provides = azap::authz::Principal,
+//!         provides = azap::guards::jwt::Claims,
+//!     },
+//! }
+//!
+//! let state = AppState {
+//!     jwt: Jwt::hs256(std::env::var("JWT_SECRET").unwrap())
+//!         .issuer("https://auth.example.com")
+//!         .audience("api"),
+//! };
+//! ```
+//!
+//! Keys that don't come from the state can be shared from a `static` in the `FromRef`
+//! impl instead.
+
+use std::{fmt, sync::Arc, time::Duration};
+
+use axum::{
+    extract::{Request, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+pub use jsonwebtoken;
+use jsonwebtoken::{errors::ErrorKind, Algorithm, DecodingKey, Validation};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+use crate::{authz::Principal, Error, Guarded};
+
+/// The keys and rules tokens are validated with. Cloning is cheap.
+#[derive(Clone)]
+pub struct Jwt(Arc<Inner>);
+
+#[derive(Clone)]
+struct Inner {
+    key: DecodingKey,
+    validation: Validation,
+}
+
+impl Jwt {
+    /// Validates HS256 tokens signed with `secret`.
+    pub fn hs256(secret: impl AsRef<[u8]>) -> Self {
+        Self::new(
+            DecodingKey::from_secret(secret.as_ref()),
+            Validation::new(Algorithm::HS256),
+        )
+    }
+
+    /// Validates RS256 tokens against a PEM encoded RSA public key.
+    pub fn rs256(public_key_pem: impl AsRef<[u8]>) -> jsonwebtoken::errors::Result<Self> {
+        Ok(Self::new(
+            DecodingKey::from_rsa_pem(public_key_pem.as_ref())?,
+            Validation::new(Algorithm::RS256),
+        ))
+    }
+
+    fn new(key: DecodingKey, mut validation: Validation) -> Self {
+        // Only checked once an audience is configured
+        validation.validate_aud = false;
+        Self(Arc::new(Inner { key, validation }))
+    }
+
+    /// Only accepts tokens whose `iss` is `issuer`.
+    pub fn issuer(mut self, issuer: &str) -> Self {
+        Arc::make_mut(&mut self.0).validation.set_issuer(&[issuer]);
+        self
+    }
+
+    /// Only accepts tokens whose `aud` contains `audience`.
+    pub fn audience(mut self, audience: &str) -> Self {
+        let validation = &mut Arc::make_mut(&mut self.0).validation;
+        validation.set_audience(&[audience]);
+        validation.validate_aud = true;
+        self
+    }
+
+    /// Sets the clock skew allowed for `exp` and `nbf`, 60 seconds by default.
+    pub fn leeway(mut self, leeway: Duration) -> Self {
+        Arc::make_mut(&mut self.0).validation.leeway = leeway.as_secs();
+        self
+    }
+
+    /// Validates `token` and returns its claims.
+    pub fn decode(&self, token: &str) -> jsonwebtoken::errors::Result<Claims> {
+        jsonwebtoken::decode::<Claims>(token, &self.0.key, &self.0.validation)
+            .map(|data| data.claims)
+    }
+}
+
+impl fmt::Debug for Jwt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Jwt")
+            .field("algorithms", &self.0.validation.algorithms)
+            .field("iss", &self.0.validation.iss)
+            .field("aud", &self.0.validation.aud)
+            .field("leeway", &self.0.validation.leeway)
+            .finish_non_exhaustive()
+    }
+}
+
+/// The claims of a validated token. Claims other than `sub` and `exp` are kept in
+/// `extra`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Claims {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exp: Option<u64>,
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
+}
+
+impl Claims {
+    /// Deserializes a claim from `extra`, `None` if it's absent or has another type.
+    pub fn get<T: DeserializeOwned>(&self, claim: &str) -> Option<T> {
+        serde_json::from_value(self.extra.get(claim)?.clone()).ok()
+    }
+
+    /// Roles from the `roles` claim, and scopes from the space separated `scope` claim or
+    /// the `scp` claim.
+    pub fn principal(&self) -> Principal {
+        let scopes = match self.get::<String>("scope") {
+            Some(scope) => scope.split_whitespace().map(str::to_string).collect(),
+            None => self
+                .get::<Vec<String>>("scp")
+                .or_else(|| Some(vec![self.get::<String>("scp")?]))
+                .unwrap_or_default(),
+        };
+
+        Principal {
+            roles: self.get("roles").unwrap_or_default(),
+            scopes,
+        }
+    }
+}
+
+/// Guard rejecting requests without a valid bearer token.
+pub async fn authenticate(State(jwt): State<Jwt>, mut req: Request, next: Next) -> Response {
+    let Some(token) = bearer(req.headers()) else {
+        return unauthorized("Bearer", "Missing bearer token");
+    };
+
+    let claims = match jwt.decode(token) {
+        Ok(claims) => claims,
+        Err(err) => {
+            let detail = match err.kind() {
+                ErrorKind::ExpiredSignature => "Token has expired",
+                ErrorKind::ImmatureSignature => "Token is not valid yet",
+                ErrorKind::InvalidIssuer => "Token has the wrong issuer",
+                ErrorKind::InvalidAudience => "Token has the wrong audience",
+                _ => "Invalid token",
+            };
+            return unauthorized("Bearer error=\"invalid_token\"", detail);
+        }
+    };
+
+    req.extensions_mut().insert(Guarded(claims.principal()));
+    req.extensions_mut().insert(Guarded(claims));
+    next.run(req).await
+}
+
+fn bearer(headers: &HeaderMap) -> Option<&str> {
+    let value = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let (scheme, token) = value.split_once(' ')?;
+
+    scheme
+        .eq_ignore_ascii_case("bearer")
+        .then(|| token.trim())
+        .filter(|token| !token.is_empty())
+}
+
+fn unauthorized(challenge: &'static str, detail: &str) -> Response {
+    let mut response = Error::new(StatusCode::UNAUTHORIZED)
+        .detail(detail)
+        .into_response();
+    response.headers_mut().insert(
+        header::WWW_AUTHENTICATE,
+        HeaderValue::from_static(challenge),
+    );
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestClient;
+    use axum::{middleware, routing::get, Router};
+    use jsonwebtoken::{encode, get_current_timestamp, EncodingKey, Header};
+    use rsa::{
+        pkcs1::{EncodeRsaPrivateKey, LineEnding},
+        pkcs8::EncodePublicKey,
+        rand_core::OsRng,
+        RsaPrivateKey,
+    };
+    use serde_json::json;
+    use std::sync::LazyLock;
+
+    /// `(private, public)` PEM keys, generated once per test run. 1024 bits keeps that
+    /// fast in debug builds.
+    static KEYS: LazyLock<(String, String)> = LazyLock::new(|| {
+        let key = RsaPrivateKey::new(&mut OsRng, 1024).unwrap();
+        let private = key.to_pkcs1_pem(LineEnding::LF).unwrap().to_string();
+        let public = key
+            .to_public_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        (private, public)
+    });
+
+    fn client(jwt: Jwt) -> TestClient {
+        let router =
+            Router::new()
+                .route(
+                    "/",
+                    get(|Guarded(claims): Guarded<Claims>| async move {
+                        claims.sub.unwrap_or_default()
+                    }),
+                )
+                .layer(middleware::from_fn_with_state(jwt, authenticate));
+        TestClient::new(router)
+    }
+
+    fn hs256(claims: Value) -> String {
+        encode(
+            &Header::default(),
+            &claims,
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    fn rs256(claims: Value) -> String {
+        let key = EncodingKey::from_rsa_pem(KEYS.0.as_bytes()).unwrap();
+        encode(&Header::new(Algorithm::RS256), &claims, &key).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_hs256() {
+        let client = client(Jwt::hs256("secret"));
+        let exp = get_current_timestamp() + 60;
+
+        let response = client.get("/").send().await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+        response.assert_header("www-authenticate", "Bearer");
+
+        let token = hs256(json!({ "sub": "alice", "exp": exp }));
+        assert_eq!(client.get("/").bearer(&token).send().await.text(), "alice");
+
+        let forged = encode(
+            &Header::default(),
+            &json!({ "sub": "alice", "exp": exp }),
+            &EncodingKey::from_secret(b"guess"),
+        )
+        .unwrap();
+        client
+            .get("/")
+            .bearer(&forged)
+            .send()
+            .await
+            .assert_header("www-authenticate", "Bearer error=\"invalid_token\"");
+    }
+
+    #[tokio::test]
+    async fn test_rs256() {
+        let jwt = Jwt::rs256(&KEYS.1)
+            .unwrap()
+            .issuer("https://auth.example.com")
+            .audience("api")
+            .leeway(Duration::from_secs(30));
+        let client = client(jwt);
+        let now = get_current_timestamp();
+        let claims = |aud: &str, exp: u64| json!({ "sub": "bob", "iss": "https://auth.example.com", "aud": aud, "exp": exp });
+
+        let token = rs256(claims("api", now + 60));
+        assert_eq!(client.get("/").bearer(&token).send().await.text(), "bob");
+
+        // Expired, but within the leeway
+        let token = rs256(claims("api", now - 10));
+        client.get("/").bearer(&token).send().await.assert_success();
+
+        let token = rs256(claims("api", now - 120));
+        let response = client.get("/").bearer(&token).send().await;
+        response.assert_status(StatusCode::UNAUTHORIZED);
+        assert_eq!(response.json::<Value>()["detail"], "Token has expired");
+
+        let token = rs256(claims("admin", now + 60));
+        let response = client.get("/").bearer(&token).send().await;
+        assert_eq!(
+            response.json::<Value>()["detail"],
+            "Token has the wrong audience"
+        );
+
+        // An HS256 token can't pass for an RS256 one
+        let token = hs256(claims("api", now + 60));
+        client
+            .get("/")
+            .bearer(&token)
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+    }
+
+    #[test]
+    fn test_principal() {
+        let claims: Claims = serde_json::from_value(json!({
+            "sub": "alice",
+            "roles": ["admin"],
+            "scope": "users:read users:write",
+        }))
+        .unwrap();
+        let principal = claims.principal();
+
+        assert_eq!(principal.roles, ["admin"]);
+        assert_eq!(principal.scopes, ["users:read", "users:write"]);
+
+        let claims: Claims = serde_json::from_value(json!({ "scp": ["users:read"] })).unwrap();
+        assert_eq!(claims.principal().scopes, ["users:read"]);
+        assert!(claims.principal().roles.is_empty());
+    }
+}
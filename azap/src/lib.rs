@@ -1,10 +1,28 @@
+pub mod authz;
+pub mod catch;
+pub mod constraints;
+pub mod deprecation;
+pub mod error;
+pub mod extract;
+pub mod guard;
+pub mod guarded;
+pub mod guards;
 pub mod prelude;
+#[cfg(any(test, feature = "testing"))]
+pub mod testing;
+pub mod trailing_slash;
+pub mod validate;
+pub use error::{Error, Result};
+pub use extract::{Json, Path, Query};
+pub use guarded::{Authenticated, Guarded};
+pub use validate::{Valid, Validate};
+
 pub use axum::{
     self,
-    extract::{Path, Query, State},
+    extract::State,
     http::StatusCode,
     middleware,
-    response::{IntoResponse, Json, Response},
+    response::{IntoResponse, Response},
     serve, Router,
 };
 
@@ -18,6 +36,34 @@ pub struct RouteMetaData {
     pub file: &'static str,
     pub line: u32,
     pub column: u32,
+    /// `{param:constraint}` pairs from `path`, e.g. `("id", "u64")`.
+    pub constraints: &'static [(&'static str, &'static str)],
+    /// Roles from `#[requires(role = "...")]`.
+    pub roles: &'static [&'static str],
+    /// Scopes from `#[requires(scope = "...")]`.
+    pub scopes: &'static [&'static str],
+}
+
+/// Expands to its items while `azap::testing` is available, i.e. with the `testing`
+/// feature. Generated routers wrap their `_with` variants, which take
+/// `testing::GuardOverrides`, in it.
+#[cfg(any(test, feature = "testing"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __testing {
+    ($($item:item)*) => {
+        $($item)*
+    };
 }
 
-pub use azap_macros::{delete, get, guards, patch, post, put, register_guard};
+#[cfg(not(any(test, feature = "testing")))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! __testing {
+    ($($item:item)*) => {};
+}
+
+pub use azap_macros::{
+    catch, delete, fallback, get, guards, mount, patch, post, public, put, register_guard,
+    register_guards, rejection, requires, routes, service,
+};
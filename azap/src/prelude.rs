@@ -1,8 +1,17 @@
-pub use crate::RouteMetaData;
+pub use crate::{
+    authz::Principal,
+    catch::Caught,
+    extract::{Json, Path, Query, Rejection},
+    guarded::{Authenticated, Guarded},
+    validate::{Valid, Validate, ValidationErrors},
+    Error, RouteMetaData,
+};
 pub use axum::{
-    extract::{Path, Query, State},
+    extract::State,
     http::StatusCode,
-    response::{IntoResponse, Json, Response},
+    response::{IntoResponse, Response},
     Router,
 };
-pub use azap_macros::{delete, get, patch, post, put};
+pub use azap_macros::{
+    catch, delete, fallback, get, mount, patch, post, public, put, rejection, requires, service,
+};
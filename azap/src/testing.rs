@@ -0,0 +1,627 @@
+//! An in-process client for testing generated routers without binding a socket.
+//!
+//! Requires the `testing` feature, usually enabled for tests only:
+//!
+//! ```toml
+//! [dev-dependencies]
+//! azap = { version = "0.1", features = ["testing"] }
+//! ```
+//!
+//! ```
+//! use azap::{testing::TestClient, Router, StatusCode};
+//!
+//! # async fn example(router: Router) {
+//! let client = TestClient::new(router);
+//!
+//! let user: serde_json::Value = client
+//!     .get("/users/1")
+//!     .header("accept", "application/json")
+//!     .send()
+//!     .await
+//!     .assert_status(StatusCode::OK)
+//!     .json();
+//! # }
+//! ```
+//!
+//! [`GuardOverrides`] swaps or disables guards in the router built by the generated
+//! `register_routes_with(state, overrides)`.
+
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    convert::Infallible,
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    body::{to_bytes, Body, Bytes},
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::IntoResponse,
+    routing::Route,
+    Router,
+};
+use serde::{de::DeserializeOwned, Serialize};
+use tower::{Layer, Service, ServiceExt};
+
+/// Sends requests to a [`Router`], e.g. the one returned by `register_routes(state)`,
+/// through `tower::ServiceExt::oneshot`.
+///
+/// `Set-Cookie` headers of every response are stored in a cookie jar shared by clones of
+/// the client and sent back with later requests. The jar keeps cookies by name only,
+/// ignoring `Domain`, `Path` and `Expires`.
+#[derive(Clone)]
+pub struct TestClient {
+    router: Router,
+    cookies: Arc<Mutex<BTreeMap<String, String>>>,
+}
+
+impl TestClient {
+    pub fn new(router: Router) -> Self {
+        Self {
+            router,
+            cookies: Arc::default(),
+        }
+    }
+
+    pub fn get(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::GET, uri)
+    }
+
+    pub fn post(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::POST, uri)
+    }
+
+    pub fn put(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::PUT, uri)
+    }
+
+    pub fn patch(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::PATCH, uri)
+    }
+
+    pub fn delete(&self, uri: &str) -> TestRequest<'_> {
+        self.request(Method::DELETE, uri)
+    }
+
+    pub fn request(&self, method: Method, uri: &str) -> TestRequest<'_> {
+        TestRequest {
+            client: self,
+            method,
+            uri: uri.to_string(),
+            headers: HeaderMap::new(),
+            body: Body::empty(),
+        }
+    }
+
+    /// Returns the value of a cookie in the jar.
+    pub fn cookie(&self, name: &str) -> Option<String> {
+        self.jar().get(name).cloned()
+    }
+
+    /// Adds a cookie to the jar, as if a response had set it.
+    pub fn set_cookie(&self, name: &str, value: &str) {
+        self.jar().insert(name.to_string(), value.to_string());
+    }
+
+    pub fn clear_cookies(&self) {
+        self.jar().clear();
+    }
+
+    fn jar(&self) -> std::sync::MutexGuard<'_, BTreeMap<String, String>> {
+        self.cookies
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    fn store_cookies(&self, headers: &HeaderMap) {
+        let mut jar = self.jar();
+
+        for set_cookie in headers.get_all(header::SET_COOKIE) {
+            let Ok(set_cookie) = set_cookie.to_str() else {
+                continue;
+            };
+
+            let mut attributes = set_cookie.split(';').map(str::trim);
+            let Some((name, value)) = attributes.next().and_then(|pair| pair.split_once('='))
+            else {
+                continue;
+            };
+
+            let expired = attributes.any(|attribute| {
+                attribute.split_once('=').is_some_and(|(key, max_age)| {
+                    key.eq_ignore_ascii_case("max-age")
+                        && max_age.parse::<i64>().is_ok_and(|max_age| max_age <= 0)
+                })
+            });
+
+            if expired {
+                jar.remove(name.trim());
+            } else {
+                jar.insert(name.trim().to_string(), value.trim().to_string());
+            }
+        }
+    }
+}
+
+/// A request being built by a [`TestClient`].
+///
+/// Invalid header names or values panic, since they are bugs in the test itself.
+pub struct TestRequest<'a> {
+    client: &'a TestClient,
+    method: Method,
+    uri: String,
+    headers: HeaderMap,
+    body: Body,
+}
+
+impl TestRequest<'_> {
+    #[track_caller]
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        let name = HeaderName::try_from(name)
+            .unwrap_or_else(|err| panic!("invalid header name `{}`: {}", name, err));
+        let value = HeaderValue::try_from(value)
+            .unwrap_or_else(|err| panic!("invalid value for header `{}`: {}", name, err));
+
+        self.headers.append(name, value);
+        self
+    }
+
+    /// Sets `Authorization: Bearer <token>`.
+    #[track_caller]
+    pub fn bearer(self, token: &str) -> Self {
+        self.header("authorization", &format!("Bearer {}", token))
+    }
+
+    /// Sends a cookie with this request only, on top of the client's cookie jar.
+    #[track_caller]
+    pub fn cookie(self, name: &str, value: &str) -> Self {
+        self.header("cookie", &format!("{}={}", name, value))
+    }
+
+    /// Sends `value` as a JSON body.
+    #[track_caller]
+    pub fn json<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        let body = serde_json::to_vec(value)
+            .unwrap_or_else(|err| panic!("failed to serialize JSON body: {}", err));
+
+        self.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/json"),
+        );
+        self.body = Body::from(body);
+        self
+    }
+
+    /// Sends `value` as an `application/x-www-form-urlencoded` body.
+    #[track_caller]
+    pub fn form<T: Serialize + ?Sized>(mut self, value: &T) -> Self {
+        let body = serde_urlencoded::to_string(value)
+            .unwrap_or_else(|err| panic!("failed to serialize form body: {}", err));
+
+        self.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        self.body = Body::from(body);
+        self
+    }
+
+    pub fn body(mut self, body: impl Into<Body>) -> Self {
+        self.body = body.into();
+        self
+    }
+
+    /// Runs the request through the router and buffers the response body.
+    pub async fn send(self) -> TestResponse {
+        let mut request = Request::builder()
+            .method(self.method)
+            .uri(&self.uri)
+            .body(self.body)
+            .unwrap_or_else(|err| panic!("invalid request URI `{}`: {}", self.uri, err));
+        *request.headers_mut() = self.headers;
+
+        let cookies = self
+            .client
+            .jar()
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect::<Vec<_>>()
+            .join("; ");
+        if let Ok(cookies) = HeaderValue::try_from(cookies)
+            && !cookies.is_empty()
+        {
+            request.headers_mut().append(header::COOKIE, cookies);
+        }
+
+        let response = match self.client.router.clone().oneshot(request).await {
+            Ok(response) => response,
+            Err(infallible) => match infallible {},
+        };
+
+        let (parts, body) = response.into_parts();
+        self.client.store_cookies(&parts.headers);
+
+        let body = to_bytes(body, usize::MAX)
+            .await
+            .unwrap_or_else(|err| panic!("failed to read response body: {}", err));
+
+        TestResponse {
+            status: parts.status,
+            headers: parts.headers,
+            body,
+        }
+    }
+}
+
+/// A buffered response returned by [`TestRequest::send`].
+pub struct TestResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: Bytes,
+}
+
+impl TestResponse {
+    pub fn status(&self) -> StatusCode {
+        self.status
+    }
+
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+
+    /// Returns a header as a string, if it is present and valid UTF-8.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers.get(name).and_then(|value| value.to_str().ok())
+    }
+
+    pub fn bytes(&self) -> &Bytes {
+        &self.body
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+
+    /// Deserializes the body as JSON, panicking with the body if it doesn't match `T`.
+    #[track_caller]
+    pub fn json<T: DeserializeOwned>(&self) -> T {
+        serde_json::from_slice(&self.body).unwrap_or_else(|err| {
+            panic!(
+                "failed to deserialize response body as JSON: {}\nbody: {}",
+                err,
+                self.text()
+            )
+        })
+    }
+
+    /// Panics with the response body unless the status is `expected`.
+    #[track_caller]
+    pub fn assert_status(&self, expected: StatusCode) -> &Self {
+        assert_eq!(
+            self.status,
+            expected,
+            "unexpected status\nbody: {}",
+            self.text()
+        );
+        self
+    }
+
+    /// Panics with the response body unless the status is 2xx.
+    #[track_caller]
+    pub fn assert_success(&self) -> &Self {
+        assert!(
+            self.status.is_success(),
+            "expected a 2xx status, got {}\nbody: {}",
+            self.status,
+            self.text()
+        );
+        self
+    }
+
+    /// Panics unless the response has a header with the `expected` value.
+    #[track_caller]
+    pub fn assert_header(&self, name: &str, expected: &str) -> &Self {
+        assert_eq!(
+            self.header(name),
+            Some(expected),
+            "unexpected `{}` header",
+            name
+        );
+        self
+    }
+}
+
+impl fmt::Debug for TestResponse {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("TestResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .field("body", &self.text())
+            .finish()
+    }
+}
+
+type Override<S> = Arc<dyn Fn(Router<S>) -> Router<S> + Send + Sync>;
+
+/// Guards to replace or disable when building a generated router with
+/// `register_routes_with(state, overrides)`.
+///
+/// A guard is named by its module path or any suffix of it, so `auth`, `admin::auth` and
+/// `guards::admin::auth` all name `auth` in `src/guards/admin.rs`. Guards registered with
+/// `register_guards!` are named by their registered path, and inline guards by the path of
+/// the function building the layer, e.g. `TraceLayer::new_for_http`.
+///
+/// ```
+/// use azap::{
+///     axum::{extract::Request, middleware::{self, Next}},
+///     testing::GuardOverrides,
+///     Response,
+/// };
+///
+/// #[derive(Clone)]
+/// struct User(&'static str);
+///
+/// async fn stub(mut req: Request, next: Next) -> Response {
+///     req.extensions_mut().insert(User("test"));
+///     next.run(req).await
+/// }
+///
+/// let overrides: GuardOverrides<()> = GuardOverrides::new()
+///     .replace("auth", middleware::from_fn(stub))
+///     .disable("rate_limit");
+/// ```
+///
+/// Building the router panics if an override matches no guard, so a renamed guard
+/// can't silently turn an override into a no-op, or if it matches guards with different
+/// paths, e.g. `auth` with both `guards::admin::auth` and `guards::api::auth`.
+pub struct GuardOverrides<S = ()> {
+    overrides: HashMap<String, Option<Override<S>>>,
+    /// The guard paths each override was applied to.
+    used: Mutex<BTreeMap<String, BTreeSet<String>>>,
+}
+
+impl<S> Default for GuardOverrides<S> {
+    fn default() -> Self {
+        Self {
+            overrides: HashMap::new(),
+            used: Mutex::default(),
+        }
+    }
+}
+
+impl<S> GuardOverrides<S>
+where
+    S: Clone + Send + Sync + 'static,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Applies `layer` wherever the guard `name` would run.
+    pub fn replace<L>(mut self, name: &str, layer: L) -> Self
+    where
+        L: Layer<Route> + Clone + Send + Sync + 'static,
+        L::Service: Service<Request> + Clone + Send + Sync + 'static,
+        <L::Service as Service<Request>>::Response: IntoResponse + 'static,
+        <L::Service as Service<Request>>::Error: Into<Infallible> + 'static,
+        <L::Service as Service<Request>>::Future: Send + 'static,
+    {
+        self.overrides.insert(
+            name.to_string(),
+            Some(Arc::new(move |router: Router<S>| {
+                router.layer(layer.clone())
+            })),
+        );
+        self
+    }
+
+    /// Skips the guard `name` entirely.
+    pub fn disable(mut self, name: &str) -> Self {
+        self.overrides.insert(name.to_string(), None);
+        self
+    }
+
+    /// Layers the guard at `path` onto `router`, or the override naming it if there is
+    /// one. The most specific name wins when several do.
+    #[doc(hidden)]
+    pub fn apply(
+        &self,
+        path: &str,
+        router: Router<S>,
+        guard: impl FnOnce(Router<S>) -> Router<S>,
+    ) -> Router<S> {
+        let Some((name, layer)) = self
+            .overrides
+            .iter()
+            .filter(|(name, _)| names_guard(name, path))
+            .max_by_key(|(name, _)| name.len())
+        else {
+            return guard(router);
+        };
+
+        self.used
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .entry(name.clone())
+            .or_default()
+            .insert(path.to_string());
+
+        match layer {
+            Some(layer) => layer(router),
+            None => router,
+        }
+    }
+
+    /// Panics if an override matched no guard of the router, or guards with different
+    /// paths.
+    #[doc(hidden)]
+    #[track_caller]
+    pub fn finish(&self) {
+        let used = self
+            .used
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        let mut unused: Vec<&str> = self
+            .overrides
+            .keys()
+            .filter(|name| !used.contains_key(*name))
+            .map(String::as_str)
+            .collect();
+        unused.sort_unstable();
+
+        assert!(
+            unused.is_empty(),
+            "guard overrides for {} match no guard of the router",
+            unused.join(", ")
+        );
+
+        if let Some((name, paths)) = used.iter().find(|(_, paths)| paths.len() > 1) {
+            let paths: Vec<&str> = paths.iter().map(String::as_str).collect();
+            let segments = name.split("::").count() + 1;
+            let example: Vec<&str> = paths[0].rsplit("::").take(segments).collect();
+
+            panic!(
+                "guard override `{}` is ambiguous, it matches {}.\n\
+                Help: Name the guard by more of its module path, e.g. `{}`.",
+                name,
+                paths.join(" and "),
+                example.into_iter().rev().collect::<Vec<_>>().join("::")
+            );
+        }
+    }
+}
+
+/// Returns `true` if the override `name` names the guard at `path`, i.e. is a suffix of
+/// it made of whole segments. A leading `crate::` is ignored.
+fn names_guard(name: &str, path: &str) -> bool {
+    let name = name.strip_prefix("crate::").unwrap_or(name);
+
+    path.strip_suffix(name)
+        .is_some_and(|rest| rest.is_empty() || rest.ends_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Json, Path};
+    use axum::{response::IntoResponse, routing::get, routing::post};
+
+    fn router() -> Router {
+        Router::new()
+            .route(
+                "/users/{id}",
+                get(|Path(id): Path<u32>| async move { Json(serde_json::json!({ "id": id })) }),
+            )
+            .route(
+                "/login",
+                post(|| async { [(header::SET_COOKIE, "session=abc; Path=/; HttpOnly")] }),
+            )
+            .route(
+                "/logout",
+                post(|| async { [(header::SET_COOKIE, "session=; Max-Age=0")] }),
+            )
+            .route(
+                "/me",
+                get(|headers: HeaderMap| async move {
+                    match headers.get(header::COOKIE) {
+                        Some(cookie) => cookie.to_str().unwrap().to_string().into_response(),
+                        None => StatusCode::UNAUTHORIZED.into_response(),
+                    }
+                }),
+            )
+    }
+
+    #[tokio::test]
+    async fn test_json_and_status() {
+        let client = TestClient::new(router());
+
+        let user: serde_json::Value = client
+            .get("/users/7")
+            .send()
+            .await
+            .assert_status(StatusCode::OK)
+            .json();
+        assert_eq!(user["id"], 7);
+
+        client
+            .get("/users/seven")
+            .send()
+            .await
+            .assert_status(StatusCode::BAD_REQUEST)
+            .assert_header("content-type", "application/problem+json");
+    }
+
+    /// Applies the guards at `paths`, returning whether each one was overridden.
+    fn build(overrides: &GuardOverrides, paths: &[&str]) -> Vec<bool> {
+        let overridden = paths
+            .iter()
+            .map(|path| {
+                let overridden = std::cell::Cell::new(true);
+                let _ = overrides.apply(path, Router::new(), |router| {
+                    overridden.set(false);
+                    router
+                });
+                overridden.get()
+            })
+            .collect();
+        overrides.finish();
+        overridden
+    }
+
+    #[test]
+    fn test_guard_overrides() {
+        let paths = [
+            "guards::admin::auth",
+            "guards::api::auth",
+            "guards::tracing",
+        ];
+
+        let overrides = GuardOverrides::new()
+            .disable("admin::auth")
+            .disable("crate::guards::tracing");
+        assert_eq!(build(&overrides, &paths), [true, false, true]);
+
+        // The more specific override wins
+        let overrides = GuardOverrides::new()
+            .disable("api::auth")
+            .disable("guards::admin::auth")
+            .disable("tracing");
+        assert_eq!(build(&overrides, &paths), [true, true, true]);
+
+        assert!(!names_guard("th", "guards::admin::auth"));
+    }
+
+    #[test]
+    #[should_panic(expected = "guard override `auth` is ambiguous, it matches \
+        guards::admin::auth and guards::api::auth.\n\
+        Help: Name the guard by more of its module path, e.g. `admin::auth`.")]
+    fn test_ambiguous_guard_override() {
+        let paths = ["guards::admin::auth", "guards::api::auth"];
+        build(&GuardOverrides::new().disable("auth"), &paths);
+    }
+
+    #[test]
+    #[should_panic(expected = "guard overrides for auht match no guard of the router")]
+    fn test_unused_guard_override() {
+        build(&GuardOverrides::new().disable("auht"), &["guards::auth"]);
+    }
+
+    #[tokio::test]
+    async fn test_cookie_jar() {
+        let client = TestClient::new(router());
+
+        client
+            .get("/me")
+            .send()
+            .await
+            .assert_status(StatusCode::UNAUTHORIZED);
+
+        client.post("/login").send().await.assert_success();
+        assert_eq!(client.cookie("session").as_deref(), Some("abc"));
+        assert_eq!(client.get("/me").send().await.text(), "session=abc");
+
+        client.post("/logout").send().await.assert_success();
+        assert_eq!(client.cookie("session"), None);
+    }
+}
@@ -0,0 +1,24 @@
+//! Support for the trailing slash policy configured in `azap_codegen`.
+
+use axum::{extract::OriginalUri, response::Redirect};
+
+/// Redirects to the same path with its trailing slash added or removed, keeping the
+/// query string.
+///
+/// `azap_codegen` only mounts this on the alternate spelling of a route, so toggling the
+/// slash always leads to the canonical path. The original URI is used so the prefix of
+/// a nested router is kept.
+pub async fn redirect(OriginalUri(uri): OriginalUri) -> Redirect {
+    let path = uri.path();
+    let mut target = match path.strip_suffix('/') {
+        Some(stripped) => stripped.to_string(),
+        None => format!("{}/", path),
+    };
+
+    if let Some(query) = uri.query() {
+        target.push('?');
+        target.push_str(query);
+    }
+
+    Redirect::permanent(&target)
+}
@@ -0,0 +1,306 @@
+//! Request validation: [`Valid<E>`] runs [`Validate`] on whatever the wrapped extractor
+//! produced and rejects with `422 Unprocessable Entity` and one error per failing field.
+//!
+//! `#[post("/", validate)]` wraps the handler's `Json<T>` argument in `Valid` for you.
+
+use std::{
+    fmt,
+    ops::{Deref, DerefMut},
+};
+
+use axum::{
+    extract::{FromRequest, FromRequestParts, Request},
+    http::request::Parts,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    extract::{Rejection, Rejections},
+    Error,
+};
+
+/// Checks a deserialized request value, e.g. a `Json<T>` body.
+///
+/// ```
+/// use azap::validate::{Validate, ValidationErrors};
+///
+/// struct CreateUser {
+///     name: String,
+///     email: String,
+/// }
+///
+/// impl Validate for CreateUser {
+///     fn validate(&self) -> Result<(), ValidationErrors> {
+///         ValidationErrors::new()
+///             .check(!self.name.is_empty(), "name", "must not be empty")
+///             .check(self.email.contains('@'), "email", "must be an email address")
+///             .into_result()
+///     }
+/// }
+/// ```
+pub trait Validate {
+    fn validate(&self) -> Result<(), ValidationErrors>;
+}
+
+impl<T: Validate> Validate for Vec<T> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+
+        for (index, item) in self.iter().enumerate() {
+            if let Err(item_errors) = item.validate() {
+                errors.nest(&format!("[{}]", index), item_errors);
+            }
+        }
+
+        errors.into_result()
+    }
+}
+
+impl<T: Validate> Validate for Option<T> {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        self.as_ref().map_or(Ok(()), Validate::validate)
+    }
+}
+
+/// A field that failed validation, e.g. `address.zip`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+/// The failing fields of a [`Validate`] implementation.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationErrors(Vec<FieldError>);
+
+impl ValidationErrors {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, field: impl Into<String>, message: impl Into<String>) -> &mut Self {
+        self.0.push(FieldError {
+            field: field.into(),
+            message: message.into(),
+        });
+        self
+    }
+
+    /// Adds an error for `field` unless `valid` holds.
+    pub fn check(
+        &mut self,
+        valid: bool,
+        field: impl Into<String>,
+        message: impl Into<String>,
+    ) -> &mut Self {
+        if !valid {
+            self.add(field, message);
+        }
+        self
+    }
+
+    /// Adds the errors of a nested value under `prefix`, e.g. `address` for `address.zip`
+    /// or `[2]` for `[2].id`.
+    pub fn nest(&mut self, prefix: &str, errors: ValidationErrors) -> &mut Self {
+        for error in errors {
+            let field = if error.field.starts_with('[') || prefix.is_empty() {
+                format!("{}{}", prefix, error.field)
+            } else {
+                format!("{}.{}", prefix, error.field)
+            };
+            self.add(field, error.message);
+        }
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &FieldError> {
+        self.0.iter()
+    }
+
+    /// `Ok(())` when no field failed.
+    pub fn into_result(&mut self) -> Result<(), ValidationErrors> {
+        if self.is_empty() {
+            Ok(())
+        } else {
+            Err(std::mem::take(self))
+        }
+    }
+}
+
+impl IntoIterator for ValidationErrors {
+    type Item = FieldError;
+    type IntoIter = std::vec::IntoIter<FieldError>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let errors: Vec<String> = self
+            .iter()
+            .map(|error| format!("`{}` {}", error.field, error.message))
+            .collect();
+        write!(f, "validation failed: {}", errors.join(", "))
+    }
+}
+
+impl std::error::Error for ValidationErrors {}
+
+impl From<ValidationErrors> for Rejection {
+    fn from(errors: ValidationErrors) -> Self {
+        Rejection::validation(errors)
+    }
+}
+
+impl From<ValidationErrors> for Error {
+    fn from(errors: ValidationErrors) -> Self {
+        Rejection::validation(errors).into_error()
+    }
+}
+
+/// Runs [`Validate`] on the value extracted by `E`, e.g. `Valid<Json<CreateUser>>`.
+///
+/// Failures are [`Rejection`]s with [`Source::Validation`](crate::extract::Source), so
+/// the `#[rejection]` handler renders them like any other rejection.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Valid<E>(pub E);
+
+impl<E> Deref for Valid<E> {
+    type Target = E;
+
+    fn deref(&self) -> &E {
+        &self.0
+    }
+}
+
+impl<E> DerefMut for Valid<E> {
+    fn deref_mut(&mut self) -> &mut E {
+        &mut self.0
+    }
+}
+
+impl<E, S> FromRequest<S> for Valid<E>
+where
+    E: FromRequest<S> + Deref,
+    E::Target: Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request(req: Request, state: &S) -> Result<Self, Self::Rejection> {
+        let handler = req.extensions().get::<Rejections>().copied();
+        let value = E::from_request(req, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        match value.validate() {
+            Ok(()) => Ok(Valid(value)),
+            Err(errors) => Err(Rejection::validation(errors)
+                .with_handler(handler)
+                .into_response()),
+        }
+    }
+}
+
+impl<E, S> FromRequestParts<S> for Valid<E>
+where
+    E: FromRequestParts<S> + Deref,
+    E::Target: Validate,
+    S: Send + Sync,
+{
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let value = E::from_request_parts(parts, state)
+            .await
+            .map_err(IntoResponse::into_response)?;
+
+        match value.validate() {
+            Ok(()) => Ok(Valid(value)),
+            Err(errors) => Err(Rejection::validation(errors)
+                .handled_by(&parts.extensions)
+                .into_response()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Json, Query};
+    use axum::{
+        body::{to_bytes, Body},
+        http::{header, StatusCode},
+    };
+    use serde::Deserialize;
+
+    #[derive(Debug, Deserialize)]
+    struct Item {
+        id: u32,
+    }
+
+    impl Validate for Item {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            ValidationErrors::new()
+                .check(self.id > 0, "id", "must be positive")
+                .into_result()
+        }
+    }
+
+    #[derive(Debug, Deserialize)]
+    struct Order {
+        items: Vec<Item>,
+    }
+
+    impl Validate for Order {
+        fn validate(&self) -> Result<(), ValidationErrors> {
+            let mut errors = ValidationErrors::new();
+            errors.check(!self.items.is_empty(), "items", "must not be empty");
+            if let Err(items) = self.items.validate() {
+                errors.nest("items", items);
+            }
+            errors.into_result()
+        }
+    }
+
+    #[tokio::test]
+    async fn test_valid_json() {
+        let req = Request::builder()
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"items":[{"id":1},{"id":0}]}"#))
+            .unwrap();
+
+        let response = Valid::<Json<Order>>::from_request(req, &())
+            .await
+            .unwrap_err();
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let body = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["source"], "validation");
+        assert_eq!(
+            body["errors"],
+            serde_json::json!([{ "field": "items[1].id", "message": "must be positive" }])
+        );
+    }
+
+    #[tokio::test]
+    async fn test_valid_query() {
+        let (mut parts, _) = Request::builder()
+            .uri("/?id=3")
+            .body(Body::empty())
+            .unwrap()
+            .into_parts();
+
+        let Valid(Query(item)) = Valid::<Query<Item>>::from_request_parts(&mut parts, &())
+            .await
+            .unwrap();
+        assert_eq!(item.id, 3);
+    }
+}
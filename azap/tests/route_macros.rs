@@ -0,0 +1,57 @@
+//! Handlers that only need to compile: the route macros' debug-build checks must accept
+//! them.
+
+use azap::axum::{body::Body, extract::State, http::Request, response::IntoResponse, routing};
+use azap::prelude::*;
+use tower::ServiceExt;
+
+#[derive(Clone)]
+pub struct AppState;
+
+#[get("/")]
+pub async fn index() -> impl IntoResponse {
+    "index"
+}
+
+#[get("/{id}")]
+pub async fn show(State(_): State<AppState>, Path(id): Path<u32>) -> impl IntoResponse {
+    id.to_string()
+}
+
+#[post("/")]
+pub async fn create(Json(body): Json<String>) -> impl IntoResponse + Send {
+    body
+}
+
+#[get("/health")]
+pub async fn health() {}
+
+#[get("/{lang?}/docs")]
+pub async fn docs(lang: Option<Path<String>>) -> String {
+    lang.map_or_else(|| "en".to_string(), |Path(lang)| lang)
+}
+
+#[test]
+fn test_route_metadata() {
+    assert_eq!(__AZAP_ROUTE_GET_INDEX.path, "/");
+    assert_eq!(__AZAP_ROUTE_GET_SHOW.path, "/{id}");
+    assert_eq!(__AZAP_ROUTE_POST_CREATE.method, "post");
+    assert_eq!(__AZAP_ROUTE_GET_HEALTH.handler_name, "health");
+}
+
+#[tokio::test]
+async fn test_optional_segment() {
+    // What azap_codegen generates for `/{lang?}/docs`
+    let router = Router::new()
+        .route("/{lang}/docs", routing::get(docs))
+        .route("/docs", routing::get(docs));
+
+    for (uri, lang) in [("/fr/docs", "fr"), ("/docs", "en")] {
+        let request = Request::get(uri).body(Body::empty()).unwrap();
+        let response = router.clone().oneshot(request).await.unwrap();
+        let body = azap::axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(body, lang);
+    }
+}
@@ -0,0 +1,217 @@
+use crate::{generate_with, Policy};
+
+/// How the generated router treats a trailing slash on route paths.
+///
+/// The canonical path is the one declared by the route, so `#[get("/")]` in
+/// `routes/users/get.rs` is served at `/users` and `/users/` is its alternate.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// Only the canonical path matches; the alternate returns 404.
+    #[default]
+    Strict,
+    /// The alternate path answers with a `308 Permanent Redirect` to the canonical one.
+    Redirect,
+    /// Both paths are served by the same handler. The root of a nested router, e.g.
+    /// `/v1/` for a version directory, redirects instead, since `nest` can't serve it.
+    Both,
+}
+
+/// How module names are turned into URL segments. Route paths written in
+/// `#[get("...")]` attributes are always used verbatim.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UrlCase {
+    /// `user_profiles` stays `user_profiles`.
+    #[default]
+    Preserve,
+    /// `user_profiles` becomes `user-profiles`.
+    Kebab,
+    /// `user_profiles` becomes `userProfiles`.
+    Camel,
+}
+
+impl UrlCase {
+    pub(crate) fn apply(&self, module: &str) -> String {
+        match self {
+            Self::Preserve => module.to_string(),
+            Self::Kebab => module.replace('_', "-"),
+            Self::Camel => module
+                .split('_')
+                .filter(|word| !word.is_empty())
+                .enumerate()
+                .map(|(i, word)| {
+                    let mut chars = word.chars();
+                    match (i, chars.next()) {
+                        (0, _) | (_, None) => word.to_string(),
+                        (_, Some(first)) => first.to_uppercase().chain(chars).collect(),
+                    }
+                })
+                .collect(),
+        }
+    }
+}
+
+/// A directory of route files under `src` and the router function generated for it.
+///
+/// `Root::new("admin_routes")` reads `src/admin_routes`, resolves guards from
+/// `src/guards` and writes `register_admin_routes(state: AppState)` to
+/// `generated_admin_routes.rs` in `OUT_DIR`. The file also holds a
+/// `__azap_admin_routes::<dir>::router(&state)` function for every directory, or
+/// `__azap::<dir>::router(&state)` for `src/routes`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Root {
+    pub(crate) dir: String,
+    pub(crate) guards: String,
+    pub(crate) function: String,
+    pub(crate) state: String,
+}
+
+impl Root {
+    pub fn new(dir: &str) -> Self {
+        Self {
+            dir: dir.to_string(),
+            guards: crate::GUARD_BASE_DIR.to_string(),
+            function: format!("register_{}", dir),
+            state: "AppState".to_string(),
+        }
+    }
+
+    /// Sets the guard directory under `src`, `guards` by default.
+    pub fn guards(mut self, dir: &str) -> Self {
+        self.guards = dir.to_string();
+        self
+    }
+
+    /// Sets the name of the generated function, `register_<dir>` by default.
+    pub fn function(mut self, name: &str) -> Self {
+        self.function = name.to_string();
+        self
+    }
+
+    /// Sets the router's state type, `AppState` by default.
+    pub fn state(mut self, ty: &str) -> Self {
+        self.state = ty.to_string();
+        self
+    }
+
+    /// Module holding the per-directory routers, `__azap` for `src/routes`.
+    pub(crate) fn module(&self) -> String {
+        match self.dir.as_str() {
+            crate::ROUTE_BASE_DIR => "__azap".to_string(),
+            dir => format!("__azap_{}", dir),
+        }
+    }
+
+    /// File in `OUT_DIR` the router is written to.
+    pub(crate) fn output_file(&self) -> String {
+        format!("generated_{}.rs", self.dir)
+    }
+}
+
+impl Default for Root {
+    fn default() -> Self {
+        Self::new(crate::ROUTE_BASE_DIR)
+    }
+}
+
+/// Build-time options for route generation, used from `build.rs`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use azap_codegen::{Config, TrailingSlash, UrlCase};
+///
+/// Config::new()
+///     .base_path("/api")
+///     .trailing_slash(TrailingSlash::Redirect)
+///     .url_case(UrlCase::Kebab)
+///     .generate();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Config {
+    pub(crate) trailing_slash: TrailingSlash,
+    pub(crate) url_case: UrlCase,
+    pub(crate) base_path: Option<String>,
+    pub(crate) roots: Vec<Root>,
+    pub(crate) policy: Policy,
+}
+
+impl Config {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the trailing slash policy, [`TrailingSlash::Strict`] by default.
+    pub fn trailing_slash(mut self, policy: TrailingSlash) -> Self {
+        self.trailing_slash = policy;
+        self
+    }
+
+    /// Sets how module names map to URL segments, [`UrlCase::Preserve`] by default.
+    ///
+    /// A directory can override its segment with `azap::routes!(path = "...")` in its
+    /// module file.
+    pub fn url_case(mut self, case: UrlCase) -> Self {
+        self.url_case = case;
+        self
+    }
+
+    /// Nests every generated route under `path`, e.g. `/api`.
+    ///
+    /// The router is built with [`Router::nest`], so `MatchedPath` reports the full path
+    /// and the fallback only applies outside the prefix. `""` and `"/"` mean no prefix.
+    ///
+    /// [`Router::nest`]: https://docs.rs/axum/latest/axum/struct.Router.html#method.nest
+    ///
+    /// # Panics
+    ///
+    /// Panics if `path` contains `{` or `}`; the prefix can't capture parameters.
+    pub fn base_path(mut self, path: &str) -> Self {
+        assert!(
+            !path.contains(['{', '}']),
+            "azap: base path \"{}\" can't contain path parameters",
+            path
+        );
+
+        let path = path.trim_matches('/');
+        self.base_path = (!path.is_empty()).then(|| format!("/{}", path));
+        self
+    }
+
+    /// Adds a route root. Without any, `src/routes` is generated as `register_routes`.
+    ///
+    /// Each root gets its own output file, so several routers can be served from one
+    /// binary, e.g. a public API and an admin API on different ports:
+    ///
+    /// ```no_run
+    /// use azap_codegen::{Config, Root};
+    ///
+    /// Config::new()
+    ///     .root(Root::new("routes"))
+    ///     .root(Root::new("admin_routes").guards("admin_guards").state("AdminState"))
+    ///     .generate();
+    /// ```
+    pub fn root(mut self, root: Root) -> Self {
+        self.roots.push(root);
+        self
+    }
+
+    /// Sets the security policy every route root is checked against. A violation fails
+    /// the build.
+    pub fn policy(mut self, policy: Policy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    pub(crate) fn roots(&self) -> Vec<Root> {
+        match self.roots.is_empty() {
+            true => vec![Root::default()],
+            false => self.roots.clone(),
+        }
+    }
+
+    /// Discovers routes and guards and writes a `generated_<root>.rs` file per root to
+    /// `OUT_DIR`.
+    pub fn generate(&self) {
+        generate_with(self);
+    }
+}
@@ -0,0 +1,481 @@
+use anyhow::{anyhow, bail, Result};
+use std::{
+    collections::HashMap,
+    fs,
+    path::{Path, PathBuf},
+};
+use syn::{punctuated::Punctuated, spanned::Spanned, MetaNameValue, Token};
+
+use crate::{router::extract_base_path, Config};
+
+/// Per-directory settings declared with `azap::routes!(...)` in a module file, i.e.
+/// `routes/users.rs` or `routes/users/mod.rs` for the `routes/users` directory.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct DirSettings {
+    /// Replaces the directory's URL segment, e.g. `path = "user-profiles"`.
+    pub path: Option<String>,
+    /// `Deprecation`/`Sunset`/`Link` headers for a version directory.
+    pub deprecation: Option<Deprecation>,
+}
+
+/// Response headers announcing that a version directory is deprecated, see RFC 9745
+/// and RFC 8594.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct Deprecation {
+    /// The `@<unix timestamp>` date the version was deprecated, as RFC 9745 requires.
+    pub deprecation: Option<String>,
+    /// An HTTP-date after which the version stops responding.
+    pub sunset: Option<String>,
+    /// Documentation for migrating off the version, sent as `rel="deprecation"`.
+    pub link: Option<String>,
+}
+
+/// A top-level version directory such as `routes/v1`, nested under its own router.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Mount {
+    pub path: String,
+    pub deprecation: Option<Deprecation>,
+}
+
+impl DirSettings {
+    fn parse(file: &Path) -> Result<Self> {
+        let mut settings = Self::default();
+
+        let Ok(content) = fs::read_to_string(file) else {
+            return Ok(settings);
+        };
+
+        // Syntax errors are left for rustc, which reports them with proper spans.
+        let Ok(syn_tree) = syn::parse_file(&content) else {
+            return Ok(settings);
+        };
+
+        for item in &syn_tree.items {
+            let syn::Item::Macro(item_macro) = item else {
+                continue;
+            };
+
+            if item_macro
+                .mac
+                .path
+                .segments
+                .last()
+                .is_none_or(|s| s.ident != "routes")
+            {
+                continue;
+            }
+
+            let args = item_macro
+                .mac
+                .parse_body_with(Punctuated::<MetaNameValue, Token![,]>::parse_terminated)
+                .map_err(|err| {
+                    let start = err.span().start();
+                    anyhow!(
+                        "{}:{}:{}: invalid routes! arguments: {}",
+                        file.display(),
+                        start.line,
+                        start.column + 1,
+                        err
+                    )
+                })?;
+
+            for arg in args {
+                let start = arg.path.span().start();
+                let location = format!("{}:{}:{}", file.display(), start.line, start.column + 1);
+                let key = arg
+                    .path
+                    .segments
+                    .iter()
+                    .map(|segment| segment.ident.to_string())
+                    .collect::<Vec<_>>()
+                    .join("::");
+
+                let syn::Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(value),
+                    ..
+                }) = &arg.value
+                else {
+                    bail!(
+                        "{}: `{}` must be a string literal.\n\
+                        Help: azap::routes!(deprecated = \"2025-06-30\");",
+                        location,
+                        key
+                    );
+                };
+                let value = value.value();
+
+                let header = match key.as_str() {
+                    "path" => {
+                        settings.path = Some(value.trim_matches('/').to_string());
+                        continue;
+                    }
+                    "deprecated" => {
+                        let date = structured_date(&value).ok_or_else(|| {
+                            anyhow!(
+                                "{}: deprecated = \"{}\" is not a date.\n\
+                                Help: The Deprecation header needs the date the version was \
+                                deprecated, as \"YYYY-MM-DD\" or \"@<unix timestamp>\":\n\
+                                azap::routes!(deprecated = \"2025-06-30\");",
+                                location,
+                                value
+                            )
+                        })?;
+                        settings.deprecation.get_or_insert_default().deprecation = Some(date);
+                        continue;
+                    }
+                    "sunset" => &mut settings.deprecation.get_or_insert_default().sunset,
+                    "link" => &mut settings.deprecation.get_or_insert_default().link,
+                    _ => bail!(
+                        "{}: unknown routes! setting `{}`.\n\
+                        Help: Expected path, deprecated, sunset or link.",
+                        location,
+                        key
+                    ),
+                };
+
+                if value.is_empty() || !value.bytes().all(|b| (b' '..=b'~').contains(&b)) {
+                    bail!(
+                        "{}: \"{}\" is not a valid header value.\n\
+                        Help: Use printable ASCII, e.g. azap::routes!(sunset = \"Wed, 31 Dec 2025 23:59:59 GMT\");",
+                        location,
+                        value
+                    );
+                }
+
+                *header = Some(value);
+            }
+        }
+
+        Ok(settings)
+    }
+}
+
+/// Converts a `YYYY-MM-DD` date (midnight UTC) or an `@<unix timestamp>` to the
+/// `@<unix timestamp>` structured field date of the `Deprecation` header.
+fn structured_date(value: &str) -> Option<String> {
+    if let Some(timestamp) = value.strip_prefix('@') {
+        let is_timestamp = !timestamp.is_empty() && timestamp.bytes().all(|b| b.is_ascii_digit());
+        return is_timestamp.then(|| value.to_string());
+    }
+
+    let mut parts = value.splitn(3, '-');
+    let (year, month, day) = (parts.next()?, parts.next()?, parts.next()?);
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return None;
+    }
+    let (year, month, day): (i64, i64, i64) =
+        (year.parse().ok()?, month.parse().ok()?, day.parse().ok()?);
+
+    let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days_in_month = match month {
+        2 if is_leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        1..=12 => 31,
+        _ => return None,
+    };
+    if !(1..=days_in_month).contains(&day) || year < 1970 {
+        return None;
+    }
+
+    // Days since 1970-01-01, counting years from March so leap days come last.
+    let (year, month) = if month <= 2 {
+        (year - 1, month + 9)
+    } else {
+        (year, month - 3)
+    };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    let days = era * 146_097 + day_of_era - 719_468;
+
+    Some(format!("@{}", days * 86_400))
+}
+
+/// Maps module paths to URL base paths, applying [`Config::url_case`] and the
+/// `routes!(path = "...")` overrides of each directory on the way.
+pub(crate) struct BasePaths<'a> {
+    route_base: &'a Path,
+    config: &'a Config,
+    settings: HashMap<PathBuf, DirSettings>,
+}
+
+impl<'a> BasePaths<'a> {
+    pub fn new(route_base: &'a Path, config: &'a Config) -> Self {
+        Self {
+            route_base,
+            config,
+            settings: HashMap::new(),
+        }
+    }
+
+    /// Returns the mount and base path for a route module, e.g. `/user-profiles` for
+    /// `routes::user_profiles::get` with kebab-case URLs.
+    ///
+    /// Routes under a top-level version directory (`routes/v1`, `routes/v2`, ...) get a
+    /// [`Mount`] and a base path relative to it, so `routes::v1::users::get` resolves to
+    /// `/v1` and `/users`.
+    pub fn resolve(&mut self, module_path: &str) -> Result<(Option<Mount>, String)> {
+        let mut mount = None;
+        let mut segments = Vec::new();
+
+        for dir in self.dirs(module_path)? {
+            if dir.is_mount() {
+                mount = Some(Mount {
+                    path: format!("/{}", dir.segment),
+                    deprecation: dir.deprecation,
+                });
+                continue;
+            }
+
+            // `path = ""` mounts the directory's routes on its parent.
+            if !dir.segment.is_empty() {
+                segments.push(dir.segment);
+            }
+        }
+
+        Ok((mount, format!("/{}", segments.join("/"))))
+    }
+
+    /// Returns the directories between the route root and a route module, e.g. `users`
+    /// for `routes::users::get`.
+    pub fn dirs(&mut self, module_path: &str) -> Result<Vec<Dir>> {
+        let mut path = self.route_base.to_path_buf();
+        let mut dirs: Vec<Dir> = Vec::new();
+
+        for module in extract_base_path(module_path)
+            .split('/')
+            .filter(|s| !s.is_empty())
+        {
+            path.push(module);
+
+            let settings = self.settings(&path)?.clone();
+            let dir = Dir {
+                module: module.to_string(),
+                segment: match settings.path {
+                    Some(segment) => segment,
+                    None => self.config.url_case.apply(module),
+                },
+                top_level: dirs.is_empty(),
+                deprecation: settings.deprecation,
+            };
+
+            if dir.deprecation.is_some() && !dir.is_mount() {
+                bail!(
+                    "`{}` sets deprecation headers but isn't a version directory.\n\
+                    Help: Move deprecated routes under a top-level directory such as routes/v1.",
+                    path.display()
+                );
+            }
+
+            dirs.push(dir);
+        }
+
+        Ok(dirs)
+    }
+
+    fn settings(&mut self, dir: &Path) -> Result<&DirSettings> {
+        if !self.settings.contains_key(dir) {
+            let mut settings = DirSettings::parse(&dir.join("mod.rs"))?;
+            if settings == DirSettings::default() {
+                settings = DirSettings::parse(&dir.with_extension("rs"))?;
+            }
+
+            self.settings.insert(dir.to_path_buf(), settings);
+        }
+
+        Ok(&self.settings[dir])
+    }
+}
+
+/// A directory a route module lives in, with the URL segment it maps to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Dir {
+    pub module: String,
+    /// Empty when the directory is mounted on its parent with `path = ""`.
+    pub segment: String,
+    pub top_level: bool,
+    pub deprecation: Option<Deprecation>,
+}
+
+impl Dir {
+    /// Top-level version directories are nested under their own router.
+    pub fn is_mount(&self) -> bool {
+        self.top_level && is_version(&self.module) && !self.segment.is_empty()
+    }
+}
+
+/// Matches version directory names like `v1` or `v12`.
+fn is_version(module: &str) -> bool {
+    module
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::UrlCase;
+
+    fn example_routes() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples/basic/src/routes")
+    }
+
+    #[test]
+    fn test_resolve_with_url_case() -> Result<()> {
+        let routes = example_routes();
+
+        let config = Config::new();
+        let mut base_paths = BasePaths::new(&routes, &config);
+        assert_eq!(
+            base_paths.resolve("routes::user_profiles::get")?.1,
+            "/user_profiles"
+        );
+        assert_eq!(base_paths.resolve("routes::health")?.1, "/health");
+
+        let config = Config::new().url_case(UrlCase::Kebab);
+        let mut base_paths = BasePaths::new(&routes, &config);
+        assert_eq!(
+            base_paths.resolve("routes::user_profiles::get")?.1,
+            "/user-profiles"
+        );
+
+        let config = Config::new().url_case(UrlCase::Camel);
+        let mut base_paths = BasePaths::new(&routes, &config);
+        assert_eq!(
+            base_paths.resolve("routes::user_profiles::get")?.1,
+            "/userProfiles"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_settings() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("azap-dirs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("user_profiles"))?;
+        fs::write(
+            dir.join("user_profiles.rs"),
+            "azap::routes!(path = \"/profiles/\");\npub mod get;\n",
+        )?;
+
+        let config = Config::new().url_case(UrlCase::Kebab);
+        let mut base_paths = BasePaths::new(&dir, &config);
+        let resolved = base_paths.resolve("routes::user_profiles::get");
+
+        fs::remove_dir_all(&dir)?;
+        assert_eq!(resolved?.1, "/profiles");
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_version_mounts() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("azap-mounts-{}", std::process::id()));
+        fs::create_dir_all(dir.join("v1/users"))?;
+        fs::create_dir_all(dir.join("v2/users"))?;
+        fs::write(
+            dir.join("v1/mod.rs"),
+            "azap::routes!(deprecated = \"2025-06-30\", sunset = \"Wed, 31 Dec 2025 23:59:59 GMT\");\n",
+        )?;
+        fs::write(
+            dir.join("v2/users/mod.rs"),
+            "azap::routes!(deprecated = \"@1751241600\");\n",
+        )?;
+
+        let config = Config::new();
+        let mut base_paths = BasePaths::new(&dir, &config);
+        let v1 = base_paths.resolve("routes::v1::users::get");
+        let v2 = base_paths.resolve("routes::v2::users::get");
+        let top = base_paths.resolve("routes::users::get");
+
+        fs::remove_dir_all(&dir)?;
+
+        let (mount, base_path) = v1?;
+        assert_eq!(base_path, "/users");
+        assert_eq!(
+            mount,
+            Some(Mount {
+                path: "/v1".to_string(),
+                deprecation: Some(Deprecation {
+                    deprecation: Some("@1751241600".to_string()),
+                    sunset: Some("Wed, 31 Dec 2025 23:59:59 GMT".to_string()),
+                    link: None,
+                }),
+            })
+        );
+        assert!(v2
+            .unwrap_err()
+            .to_string()
+            .contains("isn't a version directory"));
+        assert_eq!(top?, (None, "/users".to_string()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_structured_date() {
+        assert_eq!(structured_date("1970-01-01").as_deref(), Some("@0"));
+        assert_eq!(
+            structured_date("2025-06-30").as_deref(),
+            Some("@1751241600")
+        );
+        assert_eq!(
+            structured_date("2024-02-29").as_deref(),
+            Some("@1709164800")
+        );
+        assert_eq!(
+            structured_date("@1751241600").as_deref(),
+            Some("@1751241600")
+        );
+
+        for invalid in [
+            "true",
+            "2025-02-29",
+            "2025-13-01",
+            "2025-6-30",
+            "@",
+            "@-1",
+            "30/06/2025",
+        ] {
+            assert_eq!(structured_date(invalid), None, "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_invalid_settings() -> Result<()> {
+        let dir = std::env::temp_dir().join(format!("azap-settings-{}", std::process::id()));
+        fs::create_dir_all(&dir)?;
+
+        let mut errors = Vec::new();
+        for settings in [
+            "deprecated = true",
+            "sunset = \"Wed, 31 Dec 2025 23:59:59 GMT\", deprecated = \"soon\"",
+            "sunet = \"Wed, 31 Dec 2025 23:59:59 GMT\"",
+            "path = 1",
+        ] {
+            fs::write(
+                dir.join("mod.rs"),
+                format!("azap::routes!({});\n", settings),
+            )?;
+            errors.push(DirSettings::parse(&dir.join("mod.rs")).map_err(|err| err.to_string()));
+        }
+        fs::write(
+            dir.join("mod.rs"),
+            "azap::routes!(sunset = \"Wed, 31 Dec 2025 23:59:59 GMT\");\n",
+        )?;
+        let sunset = DirSettings::parse(&dir.join("mod.rs"));
+
+        fs::remove_dir_all(&dir)?;
+
+        let errors: Vec<String> = errors.into_iter().map(|err| err.unwrap_err()).collect();
+        assert!(errors[0].contains("`deprecated` must be a string literal"));
+        assert!(errors[1].contains("deprecated = \"soon\" is not a date"));
+        assert!(errors[2].contains("unknown routes! setting `sunet`"));
+        assert!(errors[3].contains("`path` must be a string literal"));
+
+        // A sunset alone doesn't imply a Deprecation header
+        let deprecation = sunset?.deprecation.unwrap();
+        assert_eq!(deprecation.deprecation, None);
+        assert!(deprecation.sunset.is_some());
+        Ok(())
+    }
+}
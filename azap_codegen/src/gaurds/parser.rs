@@ -1,4 +1,6 @@
 use anyhow::{bail, Result};
+use azap_syntax::types::{generic_arg, type_name};
+use quote::ToTokens;
 use std::{
     collections::HashMap,
     ffi::OsStr,
@@ -8,17 +10,25 @@ use std::{
 };
 use syn::{
     parse::{Parse, ParseStream},
+    punctuated::Punctuated,
+    visit::{self, Visit},
     Attribute, LitStr, Token,
 };
 use walkdir::WalkDir;
 
-use crate::{debug_log, GUARD_BASE_DIR};
+use crate::{attr_name, debug_log, GUARD_BASE_DIR};
 
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub enum GuardType {
     FromFn,
     FromFnWithState,
+    /// An `async fn` taking extractors and returning `azap::guarded::Authenticated<T>`.
+    Authenticate,
     Layer,
+    /// A `#[register_guard]` const implementing `azap::guard::Guard`.
+    Const,
+    /// A type with a `#[register_guard]` `impl azap::guard::Guard`, built with `FromRef`.
+    Impl,
 }
 
 impl GuardType {
@@ -26,7 +36,10 @@ impl GuardType {
         match self {
             Self::FromFn => "fn",
             Self::FromFnWithState => "fn_with_state",
+            Self::Authenticate => "authenticate",
             Self::Layer => "layer",
+            Self::Const => "const",
+            Self::Impl => "impl",
         }
     }
 
@@ -36,26 +49,12 @@ impl GuardType {
             syn::Error::new(
                 proc_macro2::Span::call_site(),
                 format!(
-                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', or 'layer'",
+                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', 'authenticate' or 'layer'",
                     s
                 ),
             )
         })
     }
-
-    pub fn parse_from_attrs(attrs: &[Attribute]) -> syn::Result<Self> {
-        for attr in attrs {
-            dbg!(attr);
-            if attr.path().is_ident("register_guard") {
-                return attr.parse_args::<GuardType>();
-            }
-        }
-
-        Err(syn::Error::new(
-            proc_macro2::Span::call_site(),
-            "Missing #[register_guard(guard_type = \"...\")] attribute",
-        ))
-    }
 }
 
 impl Parse for GuardType {
@@ -69,14 +68,14 @@ impl Parse for GuardType {
         // =
         input.parse::<Token![=]>()?;
 
-        // "fn" | "fn_with_state" | "layer"
+        // "fn" | "fn_with_state" | "authenticate" | "layer"
         let value: LitStr = input.parse()?;
 
         GuardType::from_str(&value.value()).map_err(|_| {
-            syn::Error::new(
-                proc_macro2::Span::call_site(),
+            syn::Error::new_spanned(
+                &value,
                 format!(
-                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', or 'layer'",
+                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', 'authenticate' or 'layer'",
                     value.value()
                 ),
             )
@@ -84,6 +83,100 @@ impl Parse for GuardType {
     }
 }
 
+/// The arguments of `#[register_guard(guard_type = "...", provides = User)]`.
+pub struct RegisterGuardArgs {
+    /// Only set on function guards.
+    pub guard_type: Option<GuardType>,
+    /// Types the guard stores as `azap::Guarded<T>`, keyed by [`type_key`].
+    pub provides: Vec<String>,
+}
+
+impl RegisterGuardArgs {
+    /// Reads a `#[register_guard(...)]` attribute.
+    ///
+    /// Returns `Ok(None)` when the attribute is absent, so helper functions living
+    /// next to guards are ignored rather than treated as errors.
+    pub fn parse_from_attrs(attrs: &[Attribute]) -> syn::Result<Option<Self>> {
+        attrs
+            .iter()
+            .find(|attr| attr_name(attr).is_some_and(|name| name == "register_guard"))
+            .map(|attr| match attr.meta {
+                syn::Meta::Path(_) => Ok(Self {
+                    guard_type: None,
+                    provides: Vec::new(),
+                }),
+                _ => attr.parse_args::<Self>(),
+            })
+            .transpose()
+    }
+}
+
+impl Parse for RegisterGuardArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut guard_type = None;
+        let mut provides = Vec::new();
+
+        while !input.is_empty() {
+            if input.peek(syn::Ident) && input.fork().parse::<syn::Ident>()? == "guard_type" {
+                guard_type = Some(input.parse()?);
+            } else {
+                let key: syn::Ident = input.parse()?;
+                if key != "provides" {
+                    return Err(syn::Error::new_spanned(
+                        key,
+                        "Expected 'guard_type' or 'provides'",
+                    ));
+                }
+                input.parse::<Token![=]>()?;
+                provides.push(type_key(&input.parse()?));
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(Self {
+            guard_type,
+            provides,
+        })
+    }
+}
+
+/// Compares types by their last path segment, so `User` and `crate::models::User` match.
+pub(crate) fn type_key(ty: &syn::Type) -> String {
+    let key = match ty {
+        syn::Type::Path(type_path) if type_path.qself.is_none() => {
+            match type_path.path.segments.last() {
+                Some(segment) => segment.to_token_stream().to_string(),
+                None => ty.to_token_stream().to_string(),
+            }
+        }
+        syn::Type::Paren(paren) => return type_key(&paren.elem),
+        syn::Type::Group(group) => return type_key(&group.elem),
+        _ => ty.to_token_stream().to_string(),
+    };
+
+    key.split_whitespace().collect()
+}
+
+/// The `T` of an `Authenticated<T>` or `Result<Authenticated<T>, E>` return type.
+fn authenticated_type(output: &syn::ReturnType) -> Option<&syn::Type> {
+    let syn::ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+
+    let ty = match type_name(ty).as_deref() {
+        Some("Result") => generic_arg(ty)?,
+        _ => ty.as_ref(),
+    };
+
+    match type_name(ty).as_deref() {
+        Some("Authenticated") => generic_arg(ty),
+        _ => None,
+    }
+}
+
 impl FromStr for GuardType {
     type Err = ();
 
@@ -91,6 +184,7 @@ impl FromStr for GuardType {
         match s {
             "fn" => Ok(Self::FromFn),
             "fn_with_state" => Ok(Self::FromFnWithState),
+            "authenticate" => Ok(Self::Authenticate),
             "layer" => Ok(Self::Layer),
             _ => Err(()),
         }
@@ -99,7 +193,7 @@ impl FromStr for GuardType {
 
 impl fmt::Display for GuardType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.write_str(&self.as_str())
+        f.write_str(self.as_str())
     }
 }
 
@@ -108,7 +202,7 @@ pub struct ModulePath(String);
 
 impl ModulePath {
     pub fn construct(path: &Path, base_dir: &Path, module: &str) -> Result<Self, syn::Error> {
-        let relative = path.strip_prefix(&base_dir).map_err(|_| {
+        let relative = path.strip_prefix(base_dir).map_err(|_| {
             syn::Error::new_spanned(
                 path.display().to_string(),
                 "path must be under guards directory",
@@ -117,21 +211,23 @@ impl ModulePath {
 
         let mut modules = Vec::new();
 
-        modules.push(GUARD_BASE_DIR.to_string());
+        // Guard roots live directly under `src`, so the directory names the module.
+        let root = base_dir
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(GUARD_BASE_DIR);
+        modules.push(root.to_string());
 
         for comp in relative.components() {
-            match comp {
-                Component::Normal(name) => {
-                    let name = name.to_str().ok_or_else(|| {
-                        syn::Error::new(proc_macro2::Span::call_site(), "non-UTF8 path component")
-                    })?;
+            if let Component::Normal(name) = comp {
+                let name = name.to_str().ok_or_else(|| {
+                    syn::Error::new(proc_macro2::Span::call_site(), "non-UTF8 path component")
+                })?;
 
-                    // Remove `.rs` only from the *last* component
-                    let name = name.strip_suffix(".rs").unwrap_or(name);
+                // Remove `.rs` only from the *last* component
+                let name = name.strip_suffix(".rs").unwrap_or(name);
 
-                    modules.push(name.to_owned());
-                }
-                _ => {}
+                modules.push(name.to_owned());
             }
         }
 
@@ -139,6 +235,33 @@ impl ModulePath {
 
         Ok(Self(modules.join("::")))
     }
+
+    /// Returns `true` if `reference` names this guard.
+    ///
+    /// A reference matches when its segments are a suffix of the module path, so
+    /// `auth`, `admin::auth` and `guards::admin::auth` all resolve to
+    /// `guards::admin::auth`. A leading `crate` segment is ignored.
+    pub fn matches(&self, reference: &[String]) -> bool {
+        let reference = match reference.first() {
+            Some(first) if first == "crate" => &reference[1..],
+            _ => reference,
+        };
+
+        let segments: Vec<&str> = self.0.split("::").collect();
+
+        !reference.is_empty()
+            && reference.len() <= segments.len()
+            && segments[segments.len() - reference.len()..]
+                .iter()
+                .zip(reference)
+                .all(|(segment, reference)| segment == reference)
+    }
+}
+
+impl From<&syn::Path> for ModulePath {
+    fn from(path: &syn::Path) -> Self {
+        Self(path_segments(path).join("::"))
+    }
 }
 
 impl fmt::Display for ModulePath {
@@ -147,38 +270,269 @@ impl fmt::Display for ModulePath {
     }
 }
 
+/// Where a guard is defined, which decides how it is referenced from the generated router.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum GuardSource {
+    /// A `#[register_guard]` function under `src/guards`.
+    Local,
+    /// A path registered with `register_guards!`, usually from another crate.
+    External,
+    /// A layer expression written directly in `#[guards(...)]`. It is pasted into the
+    /// generated router, where the route file's `use` items don't apply, so its paths
+    /// must be fully qualified.
+    Inline,
+}
+
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct Guard {
     pub name: syn::Ident,
     pub module_path: ModulePath,
     pub guard_type: GuardType,
+    pub source: GuardSource,
+    /// Types the guard declares with `provides = ...`. Inline guards provide nothing.
+    pub provides: Vec<String>,
 }
 
 impl Guard {
+    /// Returns the Rust expression used to reference this guard in the generated router.
+    ///
+    /// Layer guards registered by path are treated as zero-argument constructors.
+    pub fn expr(&self) -> String {
+        let path = match self.source {
+            GuardSource::Local => format!("crate::{}", self.module_path),
+            GuardSource::External => self.module_path.to_string(),
+            GuardSource::Inline => return self.module_path.to_string(),
+        };
+
+        match self.guard_type {
+            GuardType::Layer => format!("{}()", path),
+            _ => path,
+        }
+    }
+
+    /// The path `azap::testing::GuardOverrides` names this guard by: its module path, or for
+    /// inline guards the path of the function building the layer.
+    pub fn override_path(&self) -> String {
+        let GuardSource::Inline = self.source else {
+            return self.module_path.to_string();
+        };
+
+        let path = syn::parse_str::<syn::Expr>(&self.module_path.0)
+            .ok()
+            .and_then(|expr| layer_fn_path(&expr));
+
+        match path {
+            Some(path) => path.join("::"),
+            None => self.name.to_string(),
+        }
+    }
+
+    fn inline(expr: &syn::Expr) -> Self {
+        let name = layer_fn_path(expr).and_then(|path| path.last().cloned());
+
+        Self {
+            name: syn::Ident::new(
+                name.as_deref().unwrap_or("layer"),
+                proc_macro2::Span::call_site(),
+            ),
+            module_path: ModulePath(expr.to_token_stream().to_string()),
+            guard_type: GuardType::Layer,
+            source: GuardSource::Inline,
+            provides: Vec::new(),
+        }
+    }
+
     pub fn extract_from_attr(
         attr: &syn::Attribute,
         guard_store: &GuardStore,
     ) -> Result<Vec<Guard>> {
         let mut guards = Vec::new();
 
-        let args = attr.parse_args_with(
-            syn::punctuated::Punctuated::<syn::Ident, syn::Token![,]>::parse_terminated,
-        )?;
+        let args = attr.parse_args_with(Punctuated::<syn::Expr, Token![,]>::parse_terminated)?;
+
+        for expr in args {
+            // Anything other than a plain path, e.g. `TraceLayer::new_for_http()`,
+            // is an inline layer expression.
+            let syn::Expr::Path(expr_path) = &expr else {
+                check_inline_paths(&expr)?;
+                guards.push(Guard::inline(&expr));
+                continue;
+            };
+
+            let reference = path_segments(&expr_path.path);
+
+            let Some(guard) = guard_store.resolve(&reference)? else {
+                let mut known: Vec<String> = guard_store
+                    .0
+                    .values()
+                    .map(|guard| guard.name.to_string())
+                    .collect();
+                known.sort();
+
+                bail!(
+                    "unknown guard `{}`.\n\
+                    Help: Register it with #[register_guard] in the guard directory or in \
+                    register_guards! {{ ... }}. Known guards: {}",
+                    reference.join("::"),
+                    match known.is_empty() {
+                        true => "none".to_string(),
+                        false => known.join(", "),
+                    }
+                );
+            };
+            guards.push(guard.clone());
+        }
+
+        Ok(guards)
+    }
+}
+
+/// The path of the function an inline guard calls to build its layer, e.g.
+/// `tower_http::cors::CorsLayer::permissive` for
+/// `tower_http::cors::CorsLayer::permissive().allow_credentials(true)`. A leading `crate`
+/// is dropped, like in guard references.
+fn layer_fn_path(expr: &syn::Expr) -> Option<Vec<String>> {
+    let path = match expr {
+        syn::Expr::Call(call) => match call.func.as_ref() {
+            syn::Expr::Path(func) => path_segments(&func.path),
+            _ => return None,
+        },
+        syn::Expr::MethodCall(call) => return layer_fn_path(&call.receiver),
+        syn::Expr::Paren(paren) => return layer_fn_path(&paren.expr),
+        _ => return None,
+    };
+
+    match path.first() {
+        Some(first) if first == "crate" => Some(path[1..].to_vec()),
+        _ => Some(path),
+    }
+}
 
-        for ident in args {
-            let name = ident.to_string();
+/// Names usable without a `use` in every module: prelude items and primitive types.
+const PRELUDE: &[&str] = &[
+    "Some", "None", "Ok", "Err", "Option", "Result", "String", "Vec", "Box", "Default", "bool",
+    "char", "str", "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128",
+    "isize", "f32", "f64",
+];
+
+/// Fails if an inline guard uses a path that doesn't start with `::` or `crate::`.
+///
+/// Inline guards are expanded in the generated router, where the route file's `use` items
+/// don't apply, so paths are required to be absolute rather than guessing which names were
+/// imported. Only prelude names and the bindings the expression itself introduces, e.g.
+/// closure parameters, may be used bare.
+fn check_inline_paths(expr: &syn::Expr) -> Result<()> {
+    #[derive(Default)]
+    struct Visitor {
+        bindings: Vec<String>,
+        rejected: Option<String>,
+    }
 
-            if let Some(guard) = guard_store.get(&name) {
-                guards.push(guard.clone());
-            } else {
-                debug_log!("{} guard NotFound", &name);
+    impl<'ast> Visit<'ast> for Visitor {
+        fn visit_path(&mut self, path: &'ast syn::Path) {
+            let absolute = match path.segments.first() {
+                _ if path.leading_colon.is_some() => true,
+                Some(first) if first.ident == "crate" => true,
+                Some(first) if path.segments.len() == 1 => {
+                    let name = first.ident.to_string();
+                    PRELUDE.contains(&name.as_str()) || self.bindings.contains(&name)
+                }
+                _ => false,
+            };
+
+            if !absolute && self.rejected.is_none() {
+                self.rejected = Some(path_segments(path).join("::"));
             }
+            visit::visit_path(self, path);
         }
 
-        Ok(guards)
+        fn visit_pat_ident(&mut self, pat: &'ast syn::PatIdent) {
+            self.bindings.push(pat.ident.to_string());
+            visit::visit_pat_ident(self, pat);
+        }
+
+        // Macros are resolved by name and their tokens aren't parsed.
+        fn visit_macro(&mut self, _: &'ast syn::Macro) {}
+    }
+
+    let mut visitor = Visitor::default();
+    visitor.visit_expr(expr);
+
+    if let Some(path) = visitor.rejected {
+        bail!(
+            "inline guard `{}` uses `{}`, which doesn't start with `::` or `crate::`.\n\
+            Help: Inline guards are expanded in the generated router, where the route file's \
+            `use` items don't apply. Start paths with `::` for dependencies and `crate::` for \
+            your own items, e.g. #[guards(::tower_http::trace::TraceLayer::new_for_http())], \
+            or register the layer with register_guards! {{ ... }}.",
+            expr.to_token_stream()
+                .to_string()
+                .replace(" :: ", "::")
+                .replace(" (", "("),
+            path
+        );
     }
+
+    Ok(())
+}
+
+/// An entry of a `register_guards!` registry, either `path => "guard_type"` or
+/// `path => { guard_type = "...", provides = T }`.
+struct RegistryEntry {
+    path: syn::Path,
+    guard_type: GuardType,
+    provides: Vec<String>,
 }
 
+impl Parse for RegistryEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: syn::Path = input.parse()?;
+        input.parse::<Token![=>]>()?;
+
+        if input.peek(syn::token::Brace) {
+            let content;
+            let braces = syn::braced!(content in input);
+            let args: RegisterGuardArgs = content.parse()?;
+            let Some(guard_type) = args.guard_type else {
+                return Err(syn::Error::new(braces.span.join(), "Expected 'guard_type'"));
+            };
+
+            return Ok(Self {
+                path,
+                guard_type,
+                provides: args.provides,
+            });
+        }
+
+        let value: LitStr = input.parse()?;
+
+        let guard_type = GuardType::from_str(&value.value()).map_err(|_| {
+            syn::Error::new_spanned(
+                &value,
+                format!(
+                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', 'authenticate' or 'layer'",
+                    value.value()
+                ),
+            )
+        })?;
+
+        Ok(Self {
+            path,
+            guard_type,
+            provides: Vec::new(),
+        })
+    }
+}
+
+fn path_segments(path: &syn::Path) -> Vec<String> {
+    path.segments
+        .iter()
+        .map(|segment| segment.ident.to_string())
+        .collect()
+}
+
+/// Guards discovered under `src/guards` and registered through `register_guards!`,
+/// keyed by their full [`ModulePath`].
 #[derive(Debug, PartialEq, Eq, Clone)]
 pub struct GuardStore(HashMap<String, Guard>);
 
@@ -187,9 +541,37 @@ impl GuardStore {
         Self(HashMap::new())
     }
 
+    /// Discovers the guards of `(path under src/guards, source)` files written to a
+    /// temporary directory.
+    #[cfg(test)]
+    pub(crate) fn from_source(files: &[(&str, &str)]) -> Result<Self> {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        static DIRS: AtomicUsize = AtomicUsize::new(0);
+        let root = std::env::temp_dir().join(format!(
+            "azap-guards-{}-{}",
+            std::process::id(),
+            DIRS.fetch_add(1, Ordering::Relaxed)
+        ));
+        let dir = root.join(GUARD_BASE_DIR);
+
+        let mut store = Self::new();
+        let discovered = files
+            .iter()
+            .try_for_each(|(path, source)| {
+                let file = dir.join(path);
+                fs::create_dir_all(file.parent().unwrap_or(&dir))?;
+                Ok(fs::write(file, source)?)
+            })
+            .and_then(|()| store.discover_guards(&dir));
+        let _ = fs::remove_dir_all(&root);
+        discovered.map(|()| store)
+    }
+
     pub fn discover_guards(&mut self, path: &Path) -> Result<()> {
         if !path.exists() {
-            bail!("{} dir doesn't exist — skipping guards", path.display());
+            debug_log!("{} dir doesn't exist — skipping guards", path.display());
+            return Ok(());
         }
 
         for entry in WalkDir::new(path)
@@ -197,44 +579,183 @@ impl GuardStore {
             .filter_map(Result::ok)
             .filter(|entry| {
                 let path = entry.path();
-                path.is_file()
-                    && path.extension() == Some(OsStr::new("rs"))
-                    && path.file_name() != Some(OsStr::new("mod.rs"))
+                path.is_file() && path.extension() == Some(OsStr::new("rs"))
             })
         {
+            // `mod.rs` files may hold a `register_guards!` registry, but their
+            // functions don't map onto a guard module path.
+            let is_mod_file = entry.path().file_name() == Some(OsStr::new("mod.rs"));
+
             let content = fs::read_to_string(entry.path())?;
-            let syn_tree = syn::parse_file(&content)?;
 
-            for item in syn_tree.items {
-                if let syn::Item::Fn(func) = item {
-                    let guard_type = GuardType::parse_from_attrs(&func.attrs)?;
+            // Syntax errors are left for rustc, which reports them with proper spans.
+            let Ok(syn_tree) = syn::parse_file(&content) else {
+                continue;
+            };
 
-                    let fn_name = func.sig.ident.clone();
-                    let module_path =
-                        ModulePath::construct(entry.path(), path, &fn_name.to_string())?;
+            for item in syn_tree.items {
+                if let syn::Item::Macro(item_macro) = &item
+                    && item_macro
+                        .mac
+                        .path
+                        .segments
+                        .last()
+                        .is_some_and(|s| s.ident == "register_guards")
+                {
+                    self.register_external(entry.path(), &item_macro.mac)?;
+                    continue;
+                }
 
-                    let guard = Guard {
-                        name: fn_name.clone(),
-                        module_path,
-                        guard_type,
-                    };
+                if is_mod_file {
+                    continue;
+                }
 
-                    self.0.insert(fn_name.to_string(), guard);
+                // Function guards, guard consts and `impl Guard<S> for T` blocks
+                let (attrs, name, guard_type) = match &item {
+                    syn::Item::Fn(func) => (&func.attrs, func.sig.ident.clone(), None),
+                    syn::Item::Const(item) => {
+                        (&item.attrs, item.ident.clone(), Some(GuardType::Const))
+                    }
+                    syn::Item::Impl(item) => {
+                        let syn::Type::Path(ty) = item.self_ty.as_ref() else {
+                            continue;
+                        };
+                        let Some(segment) = ty.path.segments.last() else {
+                            continue;
+                        };
+                        (&item.attrs, segment.ident.clone(), Some(GuardType::Impl))
+                    }
+                    _ => continue,
+                };
+
+                let args = match RegisterGuardArgs::parse_from_attrs(attrs) {
+                    Ok(Some(args)) => args,
+                    Ok(None) => continue,
+                    Err(err) => {
+                        let start = err.span().start();
+                        bail!(
+                            "{}:{}:{}: invalid #[register_guard] on `{}`: {}",
+                            entry.path().display(),
+                            start.line,
+                            start.column + 1,
+                            name,
+                            err
+                        );
+                    }
+                };
+
+                // The macro reports a missing or misplaced `guard_type` with a span
+                let Some(guard_type) = guard_type.or(args.guard_type) else {
+                    continue;
+                };
+
+                let module_path = ModulePath::construct(entry.path(), path, &name.to_string())?;
+
+                // An `authenticate` guard provides the `T` of the `Authenticated<T>` it returns
+                let mut provides = args.provides;
+                if let syn::Item::Fn(func) = &item
+                    && guard_type == GuardType::Authenticate
+                    && let Some(ty) = authenticated_type(&func.sig.output)
+                {
+                    provides.push(type_key(ty));
                 }
+
+                self.insert(Guard {
+                    name,
+                    module_path,
+                    guard_type,
+                    source: GuardSource::Local,
+                    provides,
+                });
             }
         }
 
         Ok(())
     }
 
+    fn register_external(&mut self, file: &Path, mac: &syn::Macro) -> Result<()> {
+        let entries = mac
+            .parse_body_with(Punctuated::<RegistryEntry, Token![,]>::parse_terminated)
+            .map_err(|err| {
+                let start = err.span().start();
+                anyhow::anyhow!(
+                    "{}:{}:{}: invalid register_guards! entry: {}",
+                    file.display(),
+                    start.line,
+                    start.column + 1,
+                    err
+                )
+            })?;
+
+        for entry in entries {
+            let Some(name) = entry.path.segments.last().map(|s| s.ident.clone()) else {
+                continue;
+            };
+
+            self.insert(Guard {
+                name,
+                module_path: ModulePath::from(&entry.path),
+                guard_type: entry.guard_type,
+                source: GuardSource::External,
+                provides: entry.provides,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Adds a guard keyed by its full module path. Guards sharing a name in different
+    /// files are both kept, and [`GuardStore::resolve`] reports bare references to them.
+    pub fn insert(&mut self, guard: Guard) {
+        self.0.insert(guard.module_path.to_string(), guard);
+    }
+
     #[allow(dead_code)]
     pub fn exists(&self, guard: &str) -> bool {
-        self.0.contains_key(guard)
+        self.get(guard).is_some()
     }
 
+    /// Looks up a guard by its full module path, e.g. `guards::admin::auth`.
     pub fn get(&self, guard: &str) -> Option<&Guard> {
         self.0.get(guard)
     }
+
+    /// Resolves a (possibly qualified) guard reference from `#[guards(...)]`.
+    ///
+    /// Returns an error if the reference matches more than one guard, listing the
+    /// qualified paths that can be used instead.
+    pub fn resolve(&self, reference: &[String]) -> Result<Option<&Guard>> {
+        let mut candidates: Vec<&Guard> = self
+            .0
+            .values()
+            .filter(|guard| guard.module_path.matches(reference))
+            .collect();
+
+        match candidates.len() {
+            0 => Ok(None),
+            1 => Ok(candidates.pop()),
+            _ => {
+                let mut paths: Vec<String> = candidates
+                    .iter()
+                    .map(|guard| {
+                        let path = guard.module_path.to_string();
+                        match path.split_once("::") {
+                            Some((_, relative)) => relative.to_string(),
+                            None => path,
+                        }
+                    })
+                    .collect();
+                paths.sort();
+
+                bail!(
+                    "guard `{}` is ambiguous, it is defined in several guard files.\n\
+                    Help: Use a qualified path instead: {}",
+                    reference.join("::"),
+                    paths.join(", ")
+                )
+            }
+        }
+    }
 }
 
 impl Default for GuardStore {
@@ -250,15 +771,218 @@ mod test {
     use super::*;
     use crate::GUARD_BASE_DIR;
 
+    fn guard(module_path: &str) -> Guard {
+        let name = module_path.rsplit("::").next().unwrap();
+
+        Guard {
+            name: syn::Ident::new(name, proc_macro2::Span::call_site()),
+            module_path: ModulePath(module_path.to_string()),
+            guard_type: GuardType::FromFn,
+            source: GuardSource::Local,
+            provides: Vec::new(),
+        }
+    }
+
+    fn reference(path: &str) -> Vec<String> {
+        path.split("::").map(str::to_string).collect()
+    }
+
     #[test]
     fn test_guard_store() -> Result<()> {
-        let guard_dir = PathBuf::from("/home/gauravlohar/personal/azap/examples/basic/src")
+        let guard_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../examples/basic/src")
             .join(GUARD_BASE_DIR);
         let mut guards = GuardStore::new();
         guards.discover_guards(&guard_dir)?;
 
-        dbg!(guards);
-        assert_eq!(1, 1);
+        assert!(guards.exists("guards::auth::auth"));
+        assert!(guards.exists("guards::tracing::tracing"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_from_attrs() {
+        let guard: syn::ItemFn = syn::parse_quote! {
+            #[register_guard(guard_type = "fn_with_state")]
+            pub async fn auth() {}
+        };
+        let helper: syn::ItemFn = syn::parse_quote! {
+            fn decode_token() {}
+        };
+        let malformed: syn::ItemFn = syn::parse_quote! {
+            #[register_guard(guard_type = "middleware")]
+            pub async fn auth() {}
+        };
+
+        let args = RegisterGuardArgs::parse_from_attrs(&guard.attrs).unwrap();
+        assert_eq!(
+            args.and_then(|args| args.guard_type),
+            Some(GuardType::FromFnWithState)
+        );
+        assert!(RegisterGuardArgs::parse_from_attrs(&helper.attrs)
+            .unwrap()
+            .is_none());
+        assert!(RegisterGuardArgs::parse_from_attrs(&malformed.attrs).is_err());
+
+        let provider: syn::ItemFn = syn::parse_quote! {
+            #[register_guard(guard_type = "fn", provides = crate::models::User, provides = Claims<Admin>)]
+            pub async fn auth() {}
+        };
+        let args = RegisterGuardArgs::parse_from_attrs(&provider.attrs)
+            .unwrap()
+            .unwrap();
+        assert_eq!(args.provides, ["User", "Claims<Admin>"]);
+    }
+
+    #[test]
+    fn test_discover_trait_guards() -> Result<()> {
+        let guards = GuardStore::from_source(&[(
+            "roles.rs",
+            "pub struct RequireRole(&'static str);\n\
+            #[register_guard(provides = User)]\n\
+            impl Guard<AppState> for RequireRole {}\n\
+            #[register_guard]\n\
+            pub const ADMIN: RequireRole = RequireRole(\"admin\");\n\
+            impl Clone for RequireRole {}\n",
+        )])?;
+
+        let admin = guards.get("guards::roles::ADMIN").unwrap();
+        assert_eq!(admin.guard_type, GuardType::Const);
+        assert_eq!(admin.expr(), "crate::guards::roles::ADMIN");
+
+        let role = guards.resolve(&reference("RequireRole"))?.unwrap();
+        assert_eq!(role.guard_type, GuardType::Impl);
+        assert_eq!(role.provides, ["User"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_authenticate_guards() -> Result<()> {
+        let guards = GuardStore::from_source(&[(
+            "auth.rs",
+            "#[register_guard(guard_type = \"authenticate\")]\n\
+            pub async fn auth(headers: HeaderMap) -> Result<Authenticated<models::User>, StatusCode> {}\n\
+            #[register_guard(guard_type = \"authenticate\", provides = Principal)]\n\
+            pub async fn api_key(headers: HeaderMap) -> azap::Authenticated<Key> {}\n",
+        )])?;
+
+        let auth = guards.get("guards::auth::auth").unwrap();
+        assert_eq!(auth.guard_type, GuardType::Authenticate);
+        assert_eq!(auth.provides, ["User"]);
+
+        let api_key = guards.get("guards::auth::api_key").unwrap();
+        assert_eq!(api_key.provides, ["Principal", "Key"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_resolve_namespaced_guards() -> Result<()> {
+        let mut guards = GuardStore::new();
+        guards.insert(guard("guards::admin::auth"));
+        guards.insert(guard("guards::api::auth"));
+        guards.insert(guard("guards::tracing::tracing"));
+
+        let admin = guards.resolve(&reference("admin::auth"))?.unwrap();
+        assert_eq!(admin.module_path.to_string(), "guards::admin::auth");
+
+        let api = guards
+            .resolve(&reference("crate::guards::api::auth"))?
+            .unwrap();
+        assert_eq!(api.module_path.to_string(), "guards::api::auth");
+
+        let tracing = guards.resolve(&reference("tracing"))?.unwrap();
+        assert_eq!(tracing.module_path.to_string(), "guards::tracing::tracing");
+
+        assert!(guards.resolve(&reference("missing"))?.is_none());
+        assert!(guards.resolve(&reference("other::auth"))?.is_none());
+
+        let err = guards.resolve(&reference("auth")).unwrap_err().to_string();
+        assert!(err.contains("admin::auth, api::auth"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_unknown_guard_is_an_error() -> Result<()> {
+        let mut guards = GuardStore::new();
+        guards.insert(guard("guards::auth::auth"));
+
+        let attr: syn::Attribute = syn::parse_quote!(#[guards(auht)]);
+        let err = Guard::extract_from_attr(&attr, &guards)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("unknown guard `auht`"));
+        assert!(err.contains("Known guards: auth"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_external_guards() -> Result<()> {
+        let registry: syn::Macro = syn::parse_quote! {
+            register_guards! {
+                shared_auth::jwt => { guard_type = "fn_with_state", provides = shared_auth::Claims },
+                tower_http::trace::TraceLayer::new_for_http => "layer",
+            }
+        };
+
+        let mut guards = GuardStore::new();
+        guards.insert(guard("guards::auth::auth"));
+        guards.register_external(Path::new("guards/mod.rs"), &registry)?;
+
+        let attr: syn::Attribute = syn::parse_quote!(
+            #[guards(
+                auth,
+                shared_auth::jwt,
+                TraceLayer::new_for_http,
+                ::tower_http::cors::CorsLayer::permissive(),
+            )]
+        );
+        let resolved = Guard::extract_from_attr(&attr, &guards)?;
+        let exprs: Vec<String> = resolved.iter().map(Guard::expr).collect();
+        assert_eq!(resolved[1].provides, ["Claims"]);
+        assert!(resolved[2].provides.is_empty());
+
+        assert_eq!(
+            exprs,
+            [
+                "crate::guards::auth::auth",
+                "shared_auth::jwt",
+                "tower_http::trace::TraceLayer::new_for_http()",
+                ":: tower_http :: cors :: CorsLayer :: permissive ()",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_inline_guards_need_full_paths() -> Result<()> {
+        let guards = GuardStore::new();
+        let extract = |attr: syn::Attribute| Guard::extract_from_attr(&attr, &guards);
+
+        assert!(extract(syn::parse_quote!(#[guards(
+            ::tower_http::timeout::TimeoutLayer::new(::std::time::Duration::from_secs(5)),
+            crate::layers::cors().allow_credentials(true),
+            ::tower::util::MapRequestLayer::new(|req: ::axum::extract::Request| req),
+            ::tower::util::MapResponseLayer::new(|res| Some(res).unwrap()),
+        )]))
+        .is_ok());
+
+        let err = extract(syn::parse_quote!(#[guards(CorsLayer::permissive())]))
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with(
+            "inline guard `CorsLayer::permissive()` uses `CorsLayer::permissive`, which \
+            doesn't start with `::` or `crate::`."
+        ));
+
+        for attr in [
+            syn::parse_quote!(#[guards(tower_http::trace::TraceLayer::new_for_http())]),
+            syn::parse_quote!(#[guards(::tower_http::timeout::TimeoutLayer::new(Duration::ZERO))]),
+            syn::parse_quote!(#[guards(::tower::util::MapRequestLayer::new(|req: Request| req))]),
+            syn::parse_quote!(#[guards(super::layers::cors())]),
+            syn::parse_quote!(#[guards(cors_layer())]),
+        ] {
+            assert!(extract(attr).is_err());
+        }
         Ok(())
     }
 }
@@ -0,0 +1,241 @@
+use anyhow::{bail, Context, Result};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+use syn::LitStr;
+use walkdir::WalkDir;
+
+use crate::{attr_name, route_literal, ROUTE_METHODS};
+
+/// A route path rewritten from axum 0.7 syntax.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PathFix {
+    pub file: PathBuf,
+    pub line: usize,
+    pub old: String,
+    pub new: String,
+}
+
+/// Rewrites axum 0.7 `:param` and `*wildcard` segments to axum 0.8 `{param}` and
+/// `{*wildcard}`. Returns `None` if the path has no legacy segments.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(
+///     azap_codegen::migrate_path("/users/:id/files/*rest"),
+///     Some("/users/{id}/files/{*rest}".to_string())
+/// );
+/// assert_eq!(azap_codegen::migrate_path("/users/{id}"), None);
+/// ```
+pub fn migrate_path(path: &str) -> Option<String> {
+    let mut changed = false;
+
+    let segments: Vec<String> = path
+        .split('/')
+        .map(|segment| {
+            if let Some(param) = segment.strip_prefix(':').filter(|p| !p.is_empty()) {
+                changed = true;
+                format!("{{{}}}", param)
+            } else if let Some(param) = segment.strip_prefix('*').filter(|p| !p.is_empty()) {
+                changed = true;
+                format!("{{*{}}}", param)
+            } else {
+                segment.to_string()
+            }
+        })
+        .collect();
+
+    changed.then(|| segments.join("/"))
+}
+
+/// Fails with a migration hint if a route attribute on `func` uses legacy path syntax.
+pub(crate) fn check_route_attrs(file: &Path, func: &syn::ItemFn) -> Result<()> {
+    let Some((lit, new)) = legacy_route_literals(&func.attrs).into_iter().next() else {
+        return Ok(());
+    };
+
+    let start = lit.span().start();
+
+    bail!(
+        "{}:{}:{}: route \"{}\" on `{}` uses the `:param`/`*wildcard` syntax removed in axum 0.8.\n\
+        Help: Use \"{}\" instead, or run `cargo azap fix` to rewrite all routes.",
+        file.display(),
+        start.line,
+        start.column + 1,
+        lit.value(),
+        func.sig.ident,
+        new
+    )
+}
+
+/// Rewrites legacy route paths in every file under `dir`, returning the fixes that were
+/// applied. Only route attributes are touched, so `dir` may be the whole `src` directory
+/// and cover every route root at once.
+pub fn fix_routes(dir: &Path) -> Result<Vec<PathFix>> {
+    let mut fixes = Vec::new();
+
+    for entry in WalkDir::new(dir).into_iter().filter_map(|e| e.ok()) {
+        let path = entry.path();
+
+        if !path.is_file() || path.extension().is_none_or(|e| e != "rs") {
+            continue;
+        }
+
+        let content = fs::read_to_string(path)?;
+        let (fixed, file_fixes) =
+            fix_source(&content).with_context(|| format!("in {}", path.display()))?;
+
+        if file_fixes.is_empty() {
+            continue;
+        }
+
+        fs::write(path, fixed)?;
+        fixes.extend(file_fixes.into_iter().map(|mut fix| {
+            fix.file = path.to_path_buf();
+            fix
+        }));
+    }
+
+    Ok(fixes)
+}
+
+/// Rewrites legacy route paths in a single source file.
+fn fix_source(content: &str) -> Result<(String, Vec<PathFix>)> {
+    let syn_tree = syn::parse_file(content)?;
+
+    let legacy: Vec<(LitStr, String)> = syn_tree
+        .items
+        .iter()
+        .filter_map(|item| match item {
+            syn::Item::Fn(func) => Some(legacy_route_literals(&func.attrs)),
+            _ => None,
+        })
+        .flatten()
+        .collect();
+
+    let mut fixed = content.to_string();
+    let mut fixes = Vec::new();
+
+    // Replace back to front so earlier byte ranges stay valid.
+    for (lit, new) in legacy.into_iter().rev() {
+        let range = lit.span().byte_range();
+        fixed.replace_range(range, &format!("{:?}", new));
+
+        fixes.push(PathFix {
+            file: PathBuf::new(),
+            line: lit.span().start().line,
+            old: lit.value(),
+            new,
+        });
+    }
+
+    fixes.reverse();
+    Ok((fixed, fixes))
+}
+
+fn legacy_route_literals(attrs: &[syn::Attribute]) -> Vec<(LitStr, String)> {
+    attrs
+        .iter()
+        .filter(|attr| attr_name(attr).is_some_and(|ident| ROUTE_METHODS.contains(&ident.as_str())))
+        .filter_map(route_literal)
+        .filter_map(|lit| migrate_path(&lit.value()).map(|new| (lit, new)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate_path() {
+        assert_eq!(migrate_path("/:id"), Some("/{id}".to_string()));
+        assert_eq!(
+            migrate_path("/users/:user_id/posts/:post_id"),
+            Some("/users/{user_id}/posts/{post_id}".to_string())
+        );
+        assert_eq!(
+            migrate_path("/assets/*path"),
+            Some("/assets/{*path}".to_string())
+        );
+        assert_eq!(migrate_path("/users/{id}"), None);
+        assert_eq!(migrate_path("/"), None);
+    }
+
+    #[test]
+    fn test_check_route_attrs() {
+        let legacy: syn::ItemFn = syn::parse_quote! {
+            #[get("/:id")]
+            pub async fn get_user() {}
+        };
+        let current: syn::ItemFn = syn::parse_quote! {
+            #[get("/{id}")]
+            pub async fn get_user() {}
+        };
+
+        let err = check_route_attrs(Path::new("get.rs"), &legacy).unwrap_err();
+        assert!(err.to_string().contains("cargo azap fix"));
+        assert!(check_route_attrs(Path::new("get.rs"), &current).is_ok());
+    }
+
+    #[test]
+    fn test_fix_source() -> Result<()> {
+        let content = r#"use azap::prelude::*;
+
+#[get("/:id")]
+#[guards(auth)]
+pub async fn get_user(Path(id): Path<u32>) -> String {
+    // "/:id" in a comment is left alone
+    id.to_string()
+}
+
+#[get("/{id}/files/*path")]
+pub async fn get_file() {}
+"#;
+
+        let (fixed, fixes) = fix_source(content)?;
+
+        assert!(fixed.contains(r#"#[get("/{id}")]"#));
+        assert!(fixed.contains(r#"#[get("/{id}/files/{*path}")]"#));
+        assert!(fixed.contains(r#"// "/:id" in a comment is left alone"#));
+        assert_eq!(
+            fixes.iter().map(|fix| fix.line).collect::<Vec<_>>(),
+            [3, 10]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_fix_routes() -> Result<()> {
+        let src = std::env::temp_dir().join(format!("azap-fix-{}", std::process::id()));
+        let files = [
+            (
+                "routes/users/get.rs",
+                "#[get(\"/:id\")]\npub async fn get_user() {}\n",
+            ),
+            (
+                "admin_routes/get.rs",
+                "#[get(\"/*path\")]\npub async fn get_file() {}\n",
+            ),
+            ("main.rs", "fn main() {\n    let _ = \"/:id\";\n}\n"),
+        ];
+        for (file, content) in files {
+            let path = src.join(file);
+            fs::create_dir_all(path.parent().unwrap())?;
+            fs::write(path, content)?;
+        }
+
+        let fixes = fix_routes(&src);
+        let main = fs::read_to_string(src.join("main.rs"));
+        let admin = fs::read_to_string(src.join("admin_routes/get.rs"));
+        fs::remove_dir_all(&src)?;
+
+        let mut fixed: Vec<String> = fixes?.into_iter().map(|fix| fix.new).collect();
+        fixed.sort();
+        assert_eq!(fixed, ["/{*path}", "/{id}"]);
+        assert!(admin?.contains(r#"#[get("/{*path}")]"#));
+        assert_eq!(main?, files[2].1);
+        Ok(())
+    }
+}
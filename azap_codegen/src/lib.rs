@@ -1,27 +1,80 @@
+use anyhow::{bail, Context, Result};
 use std::{
+    collections::{hash_map::Entry, HashMap, HashSet},
     fs,
     path::{Component, Path, PathBuf},
 };
 use walkdir::WalkDir;
 
 use crate::{
-    gaurds::parser::{Guard, GuardStore},
-    router::Router,
+    dirs::{BasePaths, Dir, Mount},
+    gaurds::parser::{type_key, Guard, GuardStore},
+    params::ParamConstraint,
+    requires::Requirements,
+    router::{combine_paths, expand_optional_segments, Router},
 };
 
+pub(crate) mod config;
+pub(crate) mod dirs;
 pub(crate) mod gaurds;
+pub(crate) mod legacy;
+pub(crate) mod params;
+pub(crate) mod policy;
+pub(crate) mod requires;
 pub(crate) mod router;
 
-const ROUTE_BASE_DIR: &'static str = "routes";
-const GUARD_BASE_DIR: &'static str = "guards";
+pub use config::{Config, Root, TrailingSlash, UrlCase};
+pub use legacy::{fix_routes, migrate_path, PathFix};
+pub use policy::Policy;
+
+const ROUTE_BASE_DIR: &str = "routes";
+const GUARD_BASE_DIR: &str = "guards";
+
+/// Attribute names of the route macros.
+pub(crate) const ROUTE_METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+/// Attribute names for functions returning a `Router` or `Service` to nest.
+pub(crate) const NESTED_KINDS: &[&str] = &["mount", "service"];
+
+/// Module whose only public async fn is its directory's fallback, without `#[fallback]`.
+pub(crate) const FALLBACK_MODULE: &str = "_fallback";
 
 #[derive(Debug, Clone)]
 pub(crate) struct DiscoveredRoute {
+    /// `get`, `post`, ... or `mount`/`service` for a nested `Router`/`Service`, `fallback`,
+    /// `catch` with the status as `path`, or `rejection`.
     pub method: String,
     pub path: String,
     pub handler: String,
     pub module_path: String,
+    /// URL path of the route's directory, e.g. `/users` for `routes::users::get`.
+    pub base_path: String,
+    /// Set for routes under a version directory; `base_path` is then relative to it.
+    pub mount: Option<Mount>,
+    /// Directories from the route root to the route's module.
+    pub dirs: Vec<Dir>,
     pub guards: Vec<Guard>,
+    pub constraints: Vec<ParamConstraint>,
+    pub requirements: Requirements,
+    /// Marked `#[public]`, exempt from the [`Policy`].
+    pub public: bool,
+}
+
+impl DiscoveredRoute {
+    /// Returns `true` for `#[mount]` and `#[service]` functions.
+    pub fn is_nested(&self) -> bool {
+        NESTED_KINDS.contains(&self.method.as_str())
+    }
+
+    /// The route's path relative to the route root, including its version mount.
+    pub fn full_path(&self) -> String {
+        let base_path = match &self.mount {
+            Some(mount) => combine_paths(&mount.path, &self.base_path),
+            None => self.base_path.clone(),
+        };
+
+        combine_paths(&base_path, &self.path)
+    }
 }
 
 #[macro_export]
@@ -34,85 +87,276 @@ macro_rules!  debug_log {
     };
 }
 
+/// Generates the router with the default [`Config`].
 pub fn generate() {
+    Config::default().generate();
+}
+
+fn generate_with(config: &Config) {
     let manifest_dir = std::env::var("CARGO_MANIFEST_DIR").expect("CARGO_MANIFEST_DIR not set");
-    let routes_dir = PathBuf::from(&manifest_dir)
-        .join("src")
-        .join(ROUTE_BASE_DIR);
-    let guards_dir = PathBuf::from(manifest_dir).join("src").join(GUARD_BASE_DIR);
-    let mut guard_store = GuardStore::new();
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    let src_dir = PathBuf::from(&manifest_dir).join("src");
+    let roots = config.roots();
 
-    if let Err(err) = guard_store.discover_guards(&guards_dir) {
-        debug_log!("Error while discovering guards : {}", err);
+    if let Err(err) = check_roots(&roots) {
+        panic!("azap: invalid route roots: {:#}", err);
     }
 
-    debug_log!("Found routes dir at {}", routes_dir.display());
+    let mut guard_stores: HashMap<&str, GuardStore> = HashMap::new();
 
-    if !routes_dir.exists() {
-        println!("cargo:warning=No src/routes directory found - skipping route generation");
-        return;
-    }
+    for root in &roots {
+        let routes_dir = src_dir.join(&root.dir);
 
-    let routes = discover_routes(&routes_dir, &guard_store);
+        // Tell Cargo to rerun if routes or guards change
+        println!("cargo::rerun-if-changed=src/{}", root.dir);
+        println!("cargo::rerun-if-changed=src/{}", root.guards);
 
-    debug_log!("Found routes : {}", &routes.len());
+        let guard_store = match guard_stores.entry(&root.guards) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut guard_store = GuardStore::new();
+                if let Err(err) = guard_store.discover_guards(&src_dir.join(&root.guards)) {
+                    panic!("azap: failed to discover guards: {:#}", err);
+                }
+                entry.insert(guard_store)
+            }
+        };
 
-    let mut code = Router::new();
+        debug_log!("Found routes dir at {}", routes_dir.display());
 
-    code.generate(&routes);
+        if !routes_dir.exists() {
+            println!(
+                "cargo:warning=No src/{} directory found - skipping route generation",
+                root.dir
+            );
+            continue;
+        }
 
-    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
-    let dest_path = PathBuf::from(out_dir).join("generated_routes.rs");
+        let routes = match discover_routes(&routes_dir, guard_store, config) {
+            Ok(routes) => routes,
+            Err(err) => panic!("azap: failed to discover routes: {:#}", err),
+        };
+
+        debug_log!("Found routes : {}", &routes.len());
+
+        if let Err(err) = config.policy.check(&routes, guard_store) {
+            panic!("azap: security policy violated: {:#}", err);
+        }
+
+        let mut code = Router::new();
 
-    debug_log!("Generated Routes Destination : {}", dest_path.display());
+        code.generate(&routes, config, root);
 
-    fs::write(&dest_path, code.0).expect("Failed to write generate routes");
+        let dest_path = PathBuf::from(&out_dir).join(root.output_file());
 
-    // Tell Cargo to rerun if routes change
-    println!("cargo::rerun-if-changed=src/routes")
+        debug_log!("Generated Routes Destination : {}", dest_path.display());
+
+        fs::write(&dest_path, code.0).expect("Failed to write generate routes");
+    }
 }
 
-fn discover_routes(route: &PathBuf, guard_store: &GuardStore) -> Vec<DiscoveredRoute> {
-    dbg!(&route);
+/// Root directories become modules and their functions share a namespace, so both have
+/// to be valid and unique.
+fn check_roots(roots: &[Root]) -> Result<()> {
+    let mut dirs = HashSet::new();
+    let mut functions = HashSet::new();
+
+    for root in roots {
+        for name in [&root.dir, &root.guards, &root.function] {
+            if syn::parse_str::<syn::Ident>(name).is_err() {
+                bail!("`{}` isn't a valid Rust identifier", name);
+            }
+        }
+
+        if syn::parse_str::<syn::Type>(&root.state).is_err() {
+            bail!("`{}` isn't a valid state type", root.state);
+        }
+
+        if !dirs.insert(&root.dir) {
+            bail!(
+                "src/{} is configured as a route root more than once",
+                root.dir
+            );
+        }
+
+        if !functions.insert(&root.function) {
+            bail!("several roots generate `{}`", root.function);
+        }
+    }
+
+    Ok(())
+}
 
+fn discover_routes(
+    route: &PathBuf,
+    guard_store: &GuardStore,
+    config: &Config,
+) -> Result<Vec<DiscoveredRoute>> {
     let mut routes: Vec<DiscoveredRoute> = Vec::new();
+    let mut base_paths = BasePaths::new(route, config);
 
     for entry in WalkDir::new(route).into_iter().filter_map(|e| e.ok()) {
         let path = entry.path();
 
-        if !path.is_file() || path.extension().map_or(true, |e| e != "rs") {
+        if !path.is_file() || path.extension().is_none_or(|e| e != "rs") {
             continue;
         }
 
         // Skip mod.rs files (they just re-export)
         // TODO: handle new module structure
-        if path.file_name().map_or(false, |n| n == "mod.rs") {
+        if path.file_name().is_some_and(|n| n == "mod.rs") {
             continue;
         }
 
-        if let Ok(file_routes) = parse_route_file(path, route, guard_store) {
-            routes.extend(file_routes);
+        let file_routes = parse_route_file(path, route, guard_store, &mut base_paths)
+            .with_context(|| format!("in {}", path.display()))?;
+        routes.extend(file_routes);
+    }
+
+    check_fallbacks(&routes)?;
+    check_catchers(&routes)?;
+    check_conflicts(&routes)?;
+
+    Ok(routes)
+}
+
+/// Each directory has at most one fallback.
+fn check_fallbacks(routes: &[DiscoveredRoute]) -> Result<()> {
+    let scope = |route: &DiscoveredRoute| match &route.mount {
+        Some(mount) => combine_paths(&mount.path, &route.base_path),
+        None => route.base_path.clone(),
+    };
+    let mut fallbacks: HashMap<String, &DiscoveredRoute> = HashMap::new();
+
+    for fallback in routes.iter().filter(|route| route.method == "fallback") {
+        if let Some(other) = fallbacks.insert(scope(fallback), fallback) {
+            bail!(
+                "`crate::{}::{}` and `crate::{}::{}` are both fallbacks for {}.\n\
+                Help: Keep one #[fallback] per directory.",
+                other.module_path,
+                other.handler,
+                fallback.module_path,
+                fallback.handler,
+                scope(fallback)
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// No two handlers of a method share a path once constraints are stripped, e.g.
+/// `/{id:u64}` and `/{slug:[a-z]+}`, which axum would reject when the router is built.
+fn check_conflicts(routes: &[DiscoveredRoute]) -> Result<()> {
+    let mut handlers: HashMap<(&str, String), &DiscoveredRoute> = HashMap::new();
+
+    for route in routes
+        .iter()
+        .filter(|route| ROUTE_METHODS.contains(&route.method.as_str()))
+    {
+        for path in expand_optional_segments(&route.full_path()) {
+            let Some(other) = handlers.insert((&route.method, params::route_shape(&path)), route)
+            else {
+                continue;
+            };
+
+            bail!(
+                "`crate::{}::{}` and `crate::{}::{}` both handle {} {}.\n\
+                Help: Constraints don't tell routes apart. Give one of them another path, or \
+                take the param in a single handler and branch on it.",
+                other.module_path,
+                other.handler,
+                route.module_path,
+                route.handler,
+                route.method.to_uppercase(),
+                path
+            );
         }
     }
 
-    routes
+    Ok(())
+}
+
+/// Each status has at most one `#[catch]` handler per route root, and there's at most
+/// one `#[rejection]` handler.
+fn check_catchers(routes: &[DiscoveredRoute]) -> Result<()> {
+    let mut handlers: HashMap<(&str, &str), &DiscoveredRoute> = HashMap::new();
+
+    for handler in routes
+        .iter()
+        .filter(|route| matches!(route.method.as_str(), "catch" | "rejection"))
+    {
+        let Some(other) = handlers.insert((&handler.method, &handler.path), handler) else {
+            continue;
+        };
+
+        let what = match handler.method.as_str() {
+            "catch" => format!("catch {}", handler.path),
+            _ => "handle rejections".to_string(),
+        };
+
+        bail!(
+            "`crate::{}::{}` and `crate::{}::{}` both {}.\n\
+            Help: Keep one per route root.",
+            other.module_path,
+            other.handler,
+            handler.module_path,
+            handler.handler,
+            what
+        );
+    }
+
+    Ok(())
 }
 
 fn parse_route_file(
     file_path: &Path,
     route_base: &Path,
     guard_store: &GuardStore,
-) -> Result<Vec<DiscoveredRoute>, Box<dyn std::error::Error>> {
+    base_paths: &mut BasePaths,
+) -> Result<Vec<DiscoveredRoute>> {
     let content = fs::read_to_string(file_path)?;
-    let syn_tree = syn::parse_file(&content)?;
+
+    // Syntax errors are left for rustc, which reports them with proper spans.
+    let Ok(syn_tree) = syn::parse_file(&content) else {
+        return Ok(Vec::new());
+    };
 
     let mut routes: Vec<DiscoveredRoute> = Vec::new();
 
-    for item in syn_tree.items {
+    // In `_fallback.rs`, a public async fn is the fallback even without `#[fallback]`.
+    let implicit_fallback = file_path
+        .file_stem()
+        .is_some_and(|stem| stem == FALLBACK_MODULE)
+        && !syn_tree.items.iter().any(
+            |item| matches!(item, syn::Item::Fn(func) if func.attrs.iter().any(is_route_attr)),
+        );
+
+    for item in &syn_tree.items {
         if let syn::Item::Fn(func) = item {
-            let module_path = calculate_module_path(file_path, route_base, Some(ROUTE_BASE_DIR));
+            // Route roots live directly under `src`, so the directory names the module.
+            let root_module = route_base
+                .file_name()
+                .and_then(|name| name.to_str())
+                .unwrap_or(ROUTE_BASE_DIR);
+            let module_path = calculate_module_path(file_path, route_base, Some(root_module));
+
+            legacy::check_route_attrs(file_path, func)?;
+
+            if let Some(mut route) =
+                extract_route_from_func(func, &module_path, guard_store, implicit_fallback)?
+            {
+                (route.mount, route.base_path) = base_paths.resolve(&module_path)?;
+                route.dirs = base_paths.dirs(&module_path)?;
+                let full_path = combine_paths(&route.base_path, &route.path);
+
+                if ROUTE_METHODS.contains(&route.method.as_str())
+                    && let Some(warning) =
+                        params::check_path_params(file_path, &syn_tree, func, &full_path)?
+                {
+                    println!("cargo:warning={}", warning);
+                }
 
-            if let Some(route) = extract_route_from_func(&func, &module_path, guard_store) {
                 routes.push(route);
             }
         }
@@ -121,45 +365,251 @@ fn parse_route_file(
     Ok(routes)
 }
 
+/// The attribute's last path segment, so `#[azap::requires(...)]` is read like
+/// `#[requires(...)]`, the same way the macros match each other.
+pub(crate) fn attr_name(attr: &syn::Attribute) -> Option<String> {
+    attr.path()
+        .segments
+        .last()
+        .map(|segment| segment.ident.to_string())
+}
+
+fn is_route_attr(attr: &syn::Attribute) -> bool {
+    attr_name(attr).is_some_and(|ident| {
+        ROUTE_METHODS.contains(&ident.as_str())
+            || NESTED_KINDS.contains(&ident.as_str())
+            || ident == "fallback"
+            || ident == "catch"
+            || ident == "rejection"
+    })
+}
+
 fn extract_route_from_func(
     func: &syn::ItemFn,
     module_path: &str,
     guard_store: &GuardStore,
-) -> Option<DiscoveredRoute> {
+    implicit_fallback: bool,
+) -> Result<Option<DiscoveredRoute>> {
     let mut method = None;
     let mut path = None;
     let mut guards: Vec<Guard> = Vec::new();
+    let mut requirements = Requirements::default();
+    let mut public = false;
 
     for attr in &func.attrs {
-        let ident = match attr.path().get_ident() {
-            Some(i) => i.to_string(),
-            None => continue,
+        let Some(ident) = attr_name(attr) else {
+            continue;
         };
 
         match ident.as_str() {
-            "get" | "post" | "put" | "patch" | "delete" => {
+            "get" | "post" | "put" | "patch" | "delete" | "mount" | "service" => {
+                let Some(attr_path) = extract_path_from_attr(attr) else {
+                    return Ok(None);
+                };
+                method = Some(ident);
+                path = Some(attr_path);
+            }
+            "fallback" => {
+                method = Some(ident);
+                path = Some("/".to_string());
+            }
+            "rejection" => {
                 method = Some(ident);
-                path = Some(extract_path_from_attr(attr)?);
+                path = Some("/".to_string());
+            }
+            "catch" => {
+                let status = attr
+                    .parse_args::<syn::LitInt>()
+                    .ok()
+                    .and_then(|status| status.base10_parse::<u16>().ok())
+                    .filter(|status| (400..=599).contains(status));
+                let Some(status) = status else {
+                    bail!(
+                        "#[catch(...)] on `{}` needs a 4xx or 5xx status, e.g. #[catch(404)]",
+                        func.sig.ident
+                    );
+                };
+                method = Some(ident);
+                path = Some(status.to_string());
             }
             "guards" => {
-                if let Ok(ext_guards) = Guard::extract_from_attr(attr, guard_store) {
-                    guards.extend(ext_guards);
-                }
+                let ext_guards = Guard::extract_from_attr(attr, guard_store)
+                    .with_context(|| format!("in #[guards(...)] on `{}`", func.sig.ident))?;
+                guards.extend(ext_guards);
+            }
+            "public" => public = true,
+            "requires" => {
+                requirements
+                    .extend_from_attr(attr)
+                    .with_context(|| format!("in #[requires(...)] on `{}`", func.sig.ident))?;
             }
             _ => {}
         }
     }
 
-    Some(DiscoveredRoute {
-        method: method?,
-        path: path?,
+    if implicit_fallback
+        && method.is_none()
+        && func.sig.asyncness.is_some()
+        && matches!(func.vis, syn::Visibility::Public(_))
+    {
+        method = Some("fallback".to_string());
+        path = Some("/".to_string());
+    }
+
+    let (Some(method), Some(path)) = (method, path) else {
+        return Ok(None);
+    };
+
+    let (path, constraints) = params::strip_constraints(&path)
+        .and_then(|(stripped, constraints)| {
+            params::check_optional_segments(&stripped)?;
+            Ok((stripped, constraints))
+        })
+        .with_context(|| format!("in route \"{}\" on `{}`", path, func.sig.ident))?;
+
+    if !guards.is_empty() && matches!(method.as_str(), "catch" | "rejection") {
+        bail!(
+            "#[guards(...)] on #[{}] `{}` has no effect, it applies to the whole router.\n\
+            Help: Remove #[guards(...)].",
+            method,
+            func.sig.ident
+        );
+    }
+
+    // Guards that only trace or limit requests are fine on open routes, but one providing a
+    // value authenticates them. The policy's required guards are checked in `Policy::check`.
+    if public && let Some(guard) = guards.iter().find(|guard| !guard.provides.is_empty()) {
+        bail!(
+            "`{}` is marked #[public] but its guard `{}` provides `{}`.\n\
+            Help: Remove #[public], or the guard if the route is meant to be open.",
+            func.sig.ident,
+            guard.name,
+            guard.provides.join("`, `")
+        );
+    }
+
+    if public && matches!(method.as_str(), "catch" | "rejection") {
+        bail!(
+            "#[public] on #[{}] `{}` has no effect, the security policy only covers routes \
+            and fallbacks.\n\
+            Help: Remove #[public].",
+            method,
+            func.sig.ident
+        );
+    }
+
+    if !requirements.is_empty() && !ROUTE_METHODS.contains(&method.as_str()) {
+        bail!(
+            "#[requires(...)] on #[{}] `{}` isn't supported.\n\
+            Help: Use it on #[get], #[post], #[put], #[patch] or #[delete] handlers.",
+            method,
+            func.sig.ident
+        );
+    }
+
+    check_guarded(func, &guards, &requirements)?;
+
+    if NESTED_KINDS.contains(&method.as_str())
+        && (path == "/" || path.ends_with('/') || path.contains(['{', '}']))
+    {
+        bail!(
+            "#[{}(\"{}\")] on `{}` must be a static prefix.\n\
+            Help: Use a path like \"/legacy\" without a trailing slash or parameters.",
+            method,
+            path,
+            func.sig.ident
+        );
+    }
+
+    Ok(Some(DiscoveredRoute {
+        method,
+        path,
         handler: func.sig.ident.to_string(),
         module_path: module_path.to_string(),
+        base_path: router::extract_base_path(module_path),
+        mount: None,
+        dirs: Vec::new(),
         guards,
-    })
+        constraints,
+        requirements,
+        public,
+    }))
+}
+
+/// Every `azap::Guarded<T>` argument of a handler needs a guard on the route declared
+/// with `provides = T`, otherwise the extractor would fail on every request. The same
+/// goes for `Principal` on routes with `#[requires(...)]`.
+///
+/// Guards from `register_guards!` provide what their entry declares, inline guards
+/// provide nothing.
+fn check_guarded(func: &syn::ItemFn, guards: &[Guard], requirements: &Requirements) -> Result<()> {
+    // `(provided type, what needs it)`
+    let mut needed = Vec::new();
+
+    if !requirements.is_empty() {
+        needed.push(("Principal".to_string(), "has #[requires(...)]".to_string()));
+    }
+
+    for arg in &func.sig.inputs {
+        let syn::FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+        let syn::Type::Path(type_path) = pat_type.ty.as_ref() else {
+            continue;
+        };
+        let Some(segment) = type_path.path.segments.last() else {
+            continue;
+        };
+        if segment.ident != "Guarded" {
+            continue;
+        }
+        let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+            continue;
+        };
+        let Some(syn::GenericArgument::Type(provided)) = args.args.first() else {
+            continue;
+        };
+
+        let key = type_key(provided);
+        needed.push((key.clone(), format!("extracts `Guarded<{}>`", key)));
+    }
+
+    for (key, reason) in needed {
+        if guards.iter().any(|guard| guard.provides.contains(&key)) {
+            continue;
+        }
+
+        bail!(
+            "`{}` {} but none of its guards provides `{}`.\n\
+            Help: Add an `authenticate` guard returning `Authenticated<{}>`, a guard \
+            registered with #[register_guard(..., provides = {})], or one registered with \
+            register_guards! {{ path => {{ guard_type = \"...\", provides = {} }} }}, to \
+            #[guards(...)]{}.",
+            func.sig.ident,
+            reason,
+            key,
+            key,
+            key,
+            key,
+            match guards.is_empty() {
+                true => String::new(),
+                false => format!(
+                    ", the route has {}",
+                    guards
+                        .iter()
+                        .map(|guard| guard.name.to_string())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                ),
+            }
+        );
+    }
+
+    Ok(())
 }
 
-/// Extracts the inner path/value from a `#[attribute("...")]`.
+/// Extracts the inner path/value from a `#[attribute("...")]`, ignoring flags such as
+/// `validate` after it.
 ///
 /// # Examples
 ///
@@ -172,13 +622,17 @@ fn extract_route_from_func(
 /// assert_eq!(result, Some("/api-route".to_string()));
 /// ```
 pub(crate) fn extract_path_from_attr(attr: &syn::Attribute) -> Option<String> {
-    if let syn::Meta::List(meta_list) = &attr.meta {
-        let tokens = meta_list.tokens.to_string();
-        let path = tokens.trim_matches('"').to_string();
-        Some(path)
-    } else {
-        None
-    }
+    route_literal(attr).map(|path| path.value())
+}
+
+/// The leading string literal of a route attribute.
+pub(crate) fn route_literal(attr: &syn::Attribute) -> Option<syn::LitStr> {
+    attr.parse_args_with(|input: syn::parse::ParseStream| {
+        let path: syn::LitStr = input.parse()?;
+        input.parse::<proc_macro2::TokenStream>()?;
+        Ok(path)
+    })
+    .ok()
 }
 
 /// Calculates a Rust module path from a full file path and a route base directory.
@@ -248,11 +702,11 @@ fn calculate_module_path_from_file_path(file_path: &Path) -> String {
     let mut parts: Vec<String> = Vec::new();
 
     for component in file_path.components() {
-        if let Component::Normal(os_str) = component {
-            if let Some(comp_as_str) = os_str.to_str() {
-                let stripped_comp = comp_as_str.strip_suffix(".rs").unwrap_or(comp_as_str);
-                parts.push(stripped_comp.to_string());
-            }
+        if let Component::Normal(os_str) = component
+            && let Some(comp_as_str) = os_str.to_str()
+        {
+            let stripped_comp = comp_as_str.strip_suffix(".rs").unwrap_or(comp_as_str);
+            parts.push(stripped_comp.to_string());
         }
     }
 
@@ -265,21 +719,91 @@ mod tests {
     use super::*;
     use syn::parse_quote;
 
+    fn example_src() -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../examples/basic/src")
+    }
+
     #[test]
-    fn test_discover_routes() {
-        let path = PathBuf::from("/home/gauravlohar/personal/azap/examples/basic/src")
-            .join(ROUTE_BASE_DIR);
-        let guard_path = PathBuf::from("/home/gauravlohar/personal/azap/examples/basic/src")
-            .join(GUARD_BASE_DIR);
+    fn test_discover_routes() -> Result<()> {
+        let path = example_src().join(ROUTE_BASE_DIR);
+        let guard_path = example_src().join(GUARD_BASE_DIR);
 
         let mut guard_store = GuardStore::new();
+        guard_store.discover_guards(&guard_path)?;
 
-        if let Err(err) = guard_store.discover_guards(&guard_path) {
-            debug_log!("Error while discovering guards : {}", err);
-        }
+        let routes = discover_routes(&path, &guard_store, &Config::default())?;
+        let health = routes
+            .iter()
+            .find(|route| route.handler == "health_check")
+            .expect("health_check route");
+
+        assert_eq!(health.module_path, "routes::health");
+        assert_eq!(health.guards.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_discover_qualified_attrs() -> Result<()> {
+        let guard_store = GuardStore::from_source(&[(
+            "auth.rs",
+            "#[azap::register_guard(guard_type = \"fn\", provides = Principal)]\n\
+            pub async fn auth(req: Request, next: Next) -> Response { next.run(req).await }\n",
+        )])?;
+
+        let dir = std::env::temp_dir().join(format!("azap-qualified-{}", std::process::id()));
+        let routes_dir = dir.join(ROUTE_BASE_DIR);
+        fs::create_dir_all(&routes_dir)?;
+        fs::write(
+            routes_dir.join("admin.rs"),
+            "#[azap::get(\"/\")]\n\
+            #[azap::guards(auth)]\n\
+            #[azap::requires(role = \"admin\")]\n\
+            pub async fn dashboard() {}\n\
+            #[azap::delete(\"/{id}\")]\n\
+            #[azap::requires(role = \"admin\")]\n\
+            pub async fn delete_user() {}\n",
+        )?;
+        let discovered = discover_routes(&routes_dir, &guard_store, &Config::default());
+        fs::remove_dir_all(&dir)?;
+
+        // `delete_user` has no guard providing the `Principal` its `#[requires]` needs
+        let err = format!("{:#}", discovered.unwrap_err());
+        assert!(err.contains("`delete_user` has #[requires(...)] but none of its guards"));
+
+        let func: syn::ItemFn = parse_quote! {
+            #[azap::get("/")]
+            #[azap::guards(auth)]
+            #[azap::requires(role = "admin")]
+            pub async fn dashboard() {}
+        };
+        let route = extract_route_from_func(&func, "routes::admin", &guard_store, false)?.unwrap();
+        assert_eq!(route.method, "get");
+        assert_eq!(route.guards.len(), 1);
+        assert!(!route.requirements.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_route_with_qualified_guard() -> Result<()> {
+        let mut guard_store = GuardStore::new();
+        guard_store.discover_guards(&example_src().join(GUARD_BASE_DIR))?;
+
+        let func: syn::ItemFn = parse_quote! {
+            #[get("/")]
+            #[guards(auth::auth, crate::guards::tracing::tracing)]
+            pub async fn handler() {}
+        };
+
+        let route =
+            extract_route_from_func(&func, "routes::users::get", &guard_store, false)?.unwrap();
+        let guards: Vec<String> = route
+            .guards
+            .iter()
+            .map(|g| g.module_path.to_string())
+            .collect();
 
-        let _ = discover_routes(&path, &guard_store);
-        assert_eq!(1, 1);
+        assert_eq!(guards, ["guards::auth::auth", "guards::tracing::tracing"]);
+        Ok(())
     }
 
     #[test]
@@ -288,6 +812,212 @@ mod tests {
 
         let result: Option<String> = extract_path_from_attr(&attr);
         assert_eq!(result, Some("/api-route".to_string()));
+
+        let attr: syn::Attribute = parse_quote!(#[get("/{id:\\d+}")]);
+        assert_eq!(
+            extract_path_from_attr(&attr),
+            Some("/{id:\\d+}".to_string())
+        );
+
+        let attr: syn::Attribute = parse_quote!(#[post("/", validate)]);
+        assert_eq!(extract_path_from_attr(&attr), Some("/".to_string()));
+    }
+
+    #[test]
+    fn test_extract_route_with_constraints() -> Result<()> {
+        let func: syn::ItemFn = parse_quote! {
+            #[get("/{id:u64}/{slug:[a-z-]+}")]
+            pub async fn handler() {}
+        };
+
+        let route =
+            extract_route_from_func(&func, "routes::users::get", &GuardStore::new(), false)?
+                .unwrap();
+
+        assert_eq!(route.path, "/{id}/{slug}");
+        assert_eq!(route.constraints.len(), 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_guarded() -> Result<()> {
+        let store = GuardStore::from_source(&[
+            (
+                "auth.rs",
+                "#[register_guard(guard_type = \"fn\", provides = User, provides = Principal)]\n\
+            pub async fn auth(req: Request, next: Next) -> Response { next.run(req).await }\n\
+            #[register_guard(guard_type = \"fn\")]\n\
+            pub async fn tracing(req: Request, next: Next) -> Response { next.run(req).await }\n",
+            ),
+            (
+                "mod.rs",
+                "register_guards! {\n\
+            \x20   shared_auth::jwt => { guard_type = \"fn_with_state\", provides = Principal, provides = Claims },\n\
+            \x20   shared_auth::api_key => \"fn_with_state\",\n\
+            }\n",
+            ),
+        ])?;
+
+        let guarded: syn::ItemFn = parse_quote! {
+            #[get("/me")]
+            #[guards(auth)]
+            pub async fn me(Guarded(user): Guarded<crate::models::User>) {}
+        };
+        assert!(extract_route_from_func(&guarded, "routes::users", &store, false).is_ok());
+
+        let authorized: syn::ItemFn = parse_quote! {
+            #[delete("/{id}")]
+            #[guards(auth)]
+            #[requires(role = "admin", scope = "users:write")]
+            pub async fn delete_user() {}
+        };
+        let route = extract_route_from_func(&authorized, "routes::users", &store, false)?.unwrap();
+        assert_eq!(route.requirements.roles, ["admin"]);
+        assert_eq!(route.requirements.scopes, ["users:write"]);
+
+        let unguarded: syn::ItemFn = parse_quote! {
+            #[get("/me")]
+            #[guards(tracing)]
+            pub async fn me(Guarded(user): Guarded<User>) {}
+        };
+        let err = extract_route_from_func(&unguarded, "routes::users", &store, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("none of its guards provides `User`"));
+        assert!(err.contains("the route has tracing"));
+
+        // External guards provide what their registry entry declares
+        let external: syn::ItemFn = parse_quote! {
+            #[get("/me")]
+            #[guards(shared_auth::jwt)]
+            #[requires(role = "admin")]
+            pub async fn me(Guarded(claims): Guarded<Claims>) {}
+        };
+        assert!(extract_route_from_func(&external, "routes::users", &store, false).is_ok());
+
+        let traced: syn::ItemFn = parse_quote! {
+            #[get("/me")]
+            #[guards(shared_auth::jwt, ::tower_http::trace::TraceLayer::new_for_http())]
+            pub async fn me(Guarded(user): Guarded<User>) {}
+        };
+        let err = extract_route_from_func(&traced, "routes::users", &store, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("none of its guards provides `User`"));
+
+        let unauthorized: syn::ItemFn = parse_quote! {
+            #[delete("/{id}")]
+            #[guards(tracing)]
+            #[requires(role = "admin")]
+            pub async fn delete_user() {}
+        };
+        let err = extract_route_from_func(&unauthorized, "routes::users", &store, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("has #[requires(...)] but none of its guards provides `Principal`"));
+
+        // An external guard without `provides = Principal` doesn't satisfy `#[requires]`
+        let api_key: syn::ItemFn = parse_quote! {
+            #[delete("/{id}")]
+            #[guards(shared_auth::api_key)]
+            #[requires(role = "admin")]
+            pub async fn delete_user() {}
+        };
+        let err = extract_route_from_func(&api_key, "routes::users", &store, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("none of its guards provides `Principal`"));
+        assert!(err.contains("the route has api_key"));
+
+        // Open routes may keep guards, as long as none of them authenticates
+        let traced: syn::ItemFn = parse_quote! {
+            #[get("/status")]
+            #[public]
+            #[guards(tracing)]
+            pub async fn status() {}
+        };
+        assert!(extract_route_from_func(&traced, "routes::status", &store, false).is_ok());
+
+        let authenticated: syn::ItemFn = parse_quote! {
+            #[get("/status")]
+            #[public]
+            #[guards(tracing, auth)]
+            pub async fn status() {}
+        };
+        let err = extract_route_from_func(&authenticated, "routes::status", &store, false)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("marked #[public] but its guard `auth` provides `User`, `Principal`"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_conflicts() -> Result<()> {
+        let store = GuardStore::new();
+        let route = |func: syn::ItemFn| -> Result<DiscoveredRoute> {
+            let mut route =
+                extract_route_from_func(&func, "routes::items", &store, false)?.unwrap();
+            route.base_path = "/items".to_string();
+            Ok(route)
+        };
+
+        let by_id = route(parse_quote! {
+            #[get("/{id:u64}")]
+            pub async fn by_id(Path(id): Path<u64>) {}
+        })?;
+        let by_slug = route(parse_quote! {
+            #[get("/{slug:[a-z]+}")]
+            pub async fn by_slug(Path(slug): Path<String>) {}
+        })?;
+        let update = route(parse_quote! {
+            #[put("/{slug}")]
+            pub async fn update(Path(slug): Path<String>) {}
+        })?;
+        let archive = route(parse_quote! {
+            #[get("/{year?}/archive")]
+            pub async fn archive(year: Option<Path<u32>>) {}
+        })?;
+
+        assert!(check_conflicts(&[by_id.clone(), update.clone(), archive.clone()]).is_ok());
+
+        let err = check_conflicts(&[by_id, update, by_slug])
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with(
+            "`crate::routes::items::by_id` and `crate::routes::items::by_slug` both handle \
+            GET /items/{slug}."
+        ));
+
+        let list = route(parse_quote! {
+            #[get("/archive")]
+            pub async fn list() {}
+        })?;
+        let err = check_conflicts(&[archive, list]).unwrap_err().to_string();
+        assert!(err.contains("both handle GET /items/archive"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_fallbacks() -> Result<()> {
+        let func: syn::ItemFn = parse_quote! {
+            pub async fn not_found() -> StatusCode {
+                StatusCode::NOT_FOUND
+            }
+        };
+        let store = GuardStore::new();
+
+        let mut fallback =
+            extract_route_from_func(&func, "routes::users::_fallback", &store, true)?.unwrap();
+        fallback.base_path = "/users".to_string();
+        assert_eq!(fallback.method, "fallback");
+        assert!(extract_route_from_func(&func, "routes::users::get", &store, false)?.is_none());
+
+        assert!(check_fallbacks(&[fallback.clone()]).is_ok());
+        assert!(check_fallbacks(&[fallback.clone(), fallback])
+            .unwrap_err()
+            .to_string()
+            .contains("are both fallbacks for /users"));
+        Ok(())
     }
 
     #[test]
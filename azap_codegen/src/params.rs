@@ -0,0 +1,491 @@
+use anyhow::{bail, Result};
+use azap_syntax::{
+    is_scalar,
+    types::{generic_arg, type_name},
+    CONSTRAINT_TYPES,
+};
+use regex::Regex;
+
+use crate::router::expand_optional_segments;
+use quote::ToTokens;
+use std::path::Path;
+use syn::{Fields, Type};
+
+/// A `{name:constraint}` route parameter, e.g. `{id:u64}` or `{slug:[a-z-]+}`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct ParamConstraint {
+    pub name: String,
+    pub constraint: String,
+}
+
+/// Splits `{name:constraint}` params into an axum path and its constraints, so
+/// `/{id:u64}/{slug:[a-z-]+}` becomes `/{id}/{slug}`.
+///
+/// Constraints naming one of [`CONSTRAINT_TYPES`] are type constraints, all others must be
+/// valid regexes. A bare identifier that isn't a known type is rejected as a likely typo.
+pub(crate) fn strip_constraints(path: &str) -> Result<(String, Vec<ParamConstraint>)> {
+    let mut stripped = String::with_capacity(path.len());
+    let mut constraints = Vec::new();
+    let mut rest = 0;
+
+    for param in azap_syntax::params(path)? {
+        stripped.push_str(&path[rest..param.span.start]);
+        rest = param.span.end;
+
+        let (name, Some(constraint)) = (param.raw_name, param.constraint) else {
+            stripped.push_str(&path[param.span.clone()]);
+            continue;
+        };
+
+        if constraint.is_empty() {
+            bail!("param `{}` has an empty constraint", name);
+        }
+
+        let is_ident = constraint
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_');
+
+        if is_ident && !CONSTRAINT_TYPES.contains(&constraint) {
+            bail!(
+                "param `{}` has unknown type constraint `{}`. Expected one of {}, or a regex",
+                name,
+                constraint,
+                CONSTRAINT_TYPES.join(", ")
+            );
+        }
+
+        if !is_ident && let Err(err) = Regex::new(constraint) {
+            bail!("param `{}` has an invalid regex constraint: {}", name, err);
+        }
+
+        stripped.push_str(&format!("{{{}}}", name));
+        constraints.push(ParamConstraint {
+            name: param.name().to_string(),
+            constraint: constraint.to_string(),
+        });
+    }
+
+    stripped.push_str(&path[rest..]);
+    Ok((stripped, constraints))
+}
+
+/// Returns the `{param}` names of a route path, e.g. `["id", "rest"]` for `/{id}/{*rest}`.
+pub(crate) fn path_params(path: &str) -> Vec<String> {
+    azap_syntax::params(path)
+        .unwrap_or_default()
+        .iter()
+        .map(|param| param.name().to_string())
+        .collect()
+}
+
+/// `path` with its params left out, since axum tells `/{id}` and `/{slug}` apart by
+/// neither name nor constraint.
+pub(crate) fn route_shape(path: &str) -> String {
+    path.split('/')
+        .map(|segment| match segment.starts_with('{') {
+            true => "{}",
+            false => segment,
+        })
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Fails if the optional segments of `path` expand into routes axum would reject as
+/// conflicting, e.g. `/{a?}/{b?}` expands to both `/{a}` and `/{b}`.
+pub(crate) fn check_optional_segments(path: &str) -> Result<()> {
+    let mut shapes: Vec<(String, String)> = Vec::new();
+
+    for expanded in expand_optional_segments(path) {
+        let shape = route_shape(&expanded);
+
+        if let Some((_, other)) = shapes.iter().find(|(s, _)| *s == shape) {
+            bail!(
+                "optional segments expand to the conflicting routes `{}` and `{}`",
+                other,
+                expanded
+            );
+        }
+
+        shapes.push((shape, expanded));
+    }
+
+    Ok(())
+}
+
+/// Checks a handler's `Path<...>` extractor against the params of its final route path.
+///
+/// Mismatches that are guaranteed to fail at runtime are errors:
+///
+/// - `Path<(A, B)>` with a different number of params
+/// - `Path<u32>` (or another scalar) on a route without exactly one param
+/// - `Path<Struct>`, with `Struct` defined in the same file, whose required fields
+///   aren't all params
+/// - with optional segments, a tuple or struct that misses params on some of the
+///   expanded routes. Those without any param give `None` to `Option<Path<T>>`, the
+///   others must capture everything the extractor requires.
+///
+/// A route with params but no `Path` extractor only produces a warning, which is
+/// returned so the caller can forward it to cargo.
+pub(crate) fn check_path_params(
+    file: &Path,
+    syn_tree: &syn::File,
+    func: &syn::ItemFn,
+    full_path: &str,
+) -> Result<Option<String>> {
+    let params = path_params(full_path);
+    let fn_name = &func.sig.ident;
+
+    // `Option<Path<T>>` is how handlers extract optional segments.
+    let extractor = func.sig.inputs.iter().find_map(|arg| match arg {
+        syn::FnArg::Typed(pat_type) => match type_name(&pat_type.ty).as_deref() {
+            Some("Path") => Some((pat_type.ty.as_ref(), false)),
+            Some("Option") => generic_arg(&pat_type.ty)
+                .filter(|inner| type_name(inner).as_deref() == Some("Path"))
+                .map(|inner| (inner, true)),
+            _ => None,
+        },
+        _ => None,
+    });
+
+    let Some((extractor, is_option)) = extractor else {
+        if params.is_empty() {
+            return Ok(None);
+        }
+
+        return Ok(Some(format!(
+            "{}: route `{}` of `{}` has path params ({}) but no `Path` extractor",
+            location(file, fn_name),
+            full_path,
+            fn_name,
+            params.join(", ")
+        )));
+    };
+
+    let optional: Vec<&str> = azap_syntax::params(full_path)
+        .unwrap_or_default()
+        .iter()
+        .filter(|param| param.is_optional())
+        .map(|param| param.name())
+        .collect();
+
+    // Requests without the optional segments match a route with fewer params, which a
+    // plain `Path<T>` rejects.
+    if !optional.is_empty() && !is_option {
+        bail!(
+            "{}: `{}` extracts `{}` but route `{}` has optional segment(s) ({}).\n\
+            Help: Take `Option<{}>`, which is `None` when the segments are absent.",
+            location(file, fn_name),
+            fn_name,
+            extractor.to_token_stream().to_string().replace(' ', ""),
+            full_path,
+            optional.join(", "),
+            extractor.to_token_stream().to_string().replace(' ', "")
+        );
+    }
+
+    let Some(inner) = generic_arg(extractor) else {
+        return Ok(None);
+    };
+
+    match inner {
+        Type::Tuple(tuple) if tuple.elems.len() != params.len() => bail!(
+            "{}: `{}` extracts a tuple of {} path params but route `{}` has {}",
+            location(file, fn_name),
+            fn_name,
+            tuple.elems.len(),
+            full_path,
+            params.len()
+        ),
+        Type::Tuple(tuple) => {
+            let partial: Vec<String> = expand_optional_segments(full_path)
+                .into_iter()
+                .filter(|path| {
+                    let count = path_params(path).len();
+                    count != 0 && count != tuple.elems.len()
+                })
+                .collect();
+
+            if !partial.is_empty() {
+                bail!(
+                    "{}: `{}` extracts a tuple of {} path params but route `{}` also \
+                    matches {} with fewer.\n\
+                    Help: Extract a struct whose optional params are `Option` fields, \
+                    e.g. `Option<Path<Params>>`.",
+                    location(file, fn_name),
+                    fn_name,
+                    tuple.elems.len(),
+                    full_path,
+                    partial.join(", ")
+                );
+            }
+        }
+        ty => {
+            let Some(name) = type_name(ty) else {
+                return Ok(None);
+            };
+
+            if is_scalar(&name) {
+                if params.len() != 1 {
+                    bail!(
+                        "{}: `{}` extracts a single path param but route `{}` has {}",
+                        location(file, fn_name),
+                        fn_name,
+                        full_path,
+                        params.len()
+                    );
+                }
+            } else if let Some(fields) = struct_fields(syn_tree, &name) {
+                let missing: Vec<&String> = fields
+                    .iter()
+                    .filter(|field| !params.contains(field))
+                    .collect();
+
+                if !missing.is_empty() {
+                    bail!(
+                        "{}: `{}` extracts `Path<{}>` but route `{}` has no param for field(s): {}",
+                        location(file, fn_name),
+                        fn_name,
+                        name,
+                        full_path,
+                        missing
+                            .iter()
+                            .map(|field| field.as_str())
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+
+                // Expanded routes without any param give `None` instead
+                for path in expand_optional_segments(full_path) {
+                    let params = path_params(&path);
+                    let missing: Vec<&str> = fields
+                        .iter()
+                        .filter(|field| !params.is_empty() && !params.contains(field))
+                        .map(|field| field.as_str())
+                        .collect();
+
+                    if !missing.is_empty() {
+                        bail!(
+                            "{}: `{}` extracts `Path<{}>` but route `{}` also matches {}, \
+                            without field(s): {}.\n\
+                            Help: Make the field(s) `Option`.",
+                            location(file, fn_name),
+                            fn_name,
+                            name,
+                            full_path,
+                            path,
+                            missing.join(", ")
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(None)
+}
+
+fn location(file: &Path, fn_name: &syn::Ident) -> String {
+    let start = fn_name.span().start();
+    format!("{}:{}:{}", file.display(), start.line, start.column + 1)
+}
+
+/// Required field names of a named struct defined in `syn_tree`, honouring
+/// `#[serde(rename = "...")]`. `Option` fields are skipped since serde defaults them.
+fn struct_fields(syn_tree: &syn::File, name: &str) -> Option<Vec<String>> {
+    let item = syn_tree.items.iter().find_map(|item| match item {
+        syn::Item::Struct(item) if item.ident == name => Some(item),
+        _ => None,
+    })?;
+
+    let Fields::Named(fields) = &item.fields else {
+        return None;
+    };
+
+    let names = fields
+        .named
+        .iter()
+        .filter(|field| type_name(&field.ty).as_deref() != Some("Option"))
+        .filter_map(|field| {
+            serde_rename(&field.attrs).or(field.ident.as_ref().map(|i| i.to_string()))
+        })
+        .collect();
+
+    Some(names)
+}
+
+fn serde_rename(attrs: &[syn::Attribute]) -> Option<String> {
+    let mut rename = None;
+
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("serde")) {
+        let _ = attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: syn::LitStr = meta.value()?.parse()?;
+                rename = Some(value.value());
+            } else if meta.input.peek(syn::Token![=]) {
+                let _: syn::Expr = meta.value()?.parse()?;
+            }
+            Ok(())
+        });
+    }
+
+    rename
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn check(syn_tree: &syn::File, full_path: &str) -> Result<Option<String>> {
+        let func = syn_tree
+            .items
+            .iter()
+            .find_map(|item| match item {
+                syn::Item::Fn(func) => Some(func),
+                _ => None,
+            })
+            .unwrap();
+
+        check_path_params(Path::new("get.rs"), syn_tree, func, full_path)
+    }
+
+    #[test]
+    fn test_path_params() {
+        assert_eq!(path_params("/users/{id}/posts/{*rest}"), ["id", "rest"]);
+        assert!(path_params("/users").is_empty());
+    }
+
+    #[test]
+    fn test_strip_constraints() -> Result<()> {
+        let (path, constraints) = strip_constraints("/users/{id:u64}/{code:[a-z]{2}}/{rest}")?;
+
+        assert_eq!(path, "/users/{id}/{code}/{rest}");
+        assert_eq!(
+            constraints,
+            [
+                ParamConstraint {
+                    name: "id".to_string(),
+                    constraint: "u64".to_string()
+                },
+                ParamConstraint {
+                    name: "code".to_string(),
+                    constraint: "[a-z]{2}".to_string()
+                },
+            ]
+        );
+
+        assert!(strip_constraints("/{id:u65}").is_err());
+        assert!(strip_constraints("/{id:[a-z}").is_err());
+        assert!(strip_constraints("/{id:}").is_err());
+        assert!(strip_constraints("/{id").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_optional_segments() -> Result<()> {
+        let (path, constraints) = strip_constraints("/{lang?:[a-z]{2}}/docs")?;
+
+        assert_eq!(path, "/{lang?}/docs");
+        assert_eq!(constraints[0].name, "lang");
+        assert_eq!(path_params(&path), ["lang"]);
+
+        assert!(check_optional_segments("/{lang?}/docs/{page?}").is_ok());
+        assert!(check_optional_segments("/docs/{lang?}/{page?}").is_err());
+
+        let syn_tree: syn::File = parse_quote! {
+            pub async fn docs(lang: Option<Path<String>>) {}
+        };
+        assert!(check(&syn_tree, "/{lang?}/docs")?.is_none());
+
+        let syn_tree: syn::File = parse_quote! {
+            pub async fn docs(Path(lang): Path<String>) {}
+        };
+        let err = check(&syn_tree, "/{lang?}/docs").unwrap_err().to_string();
+        assert!(err.contains(
+            "`docs` extracts `Path<String>` but route `/{lang?}/docs` has optional segment(s) \
+            (lang)."
+        ));
+        assert!(err.contains("Help: Take `Option<Path<String>>`"));
+
+        // `/docs/{page}` and `/{lang}/docs` capture one of the two
+        let syn_tree: syn::File = parse_quote! {
+            pub async fn docs(params: Option<Path<(String, u32)>>) {}
+        };
+        let err = check(&syn_tree, "/{lang?}/docs/{page?}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains(
+            "`docs` extracts a tuple of 2 path params but route `/{lang?}/docs/{page?}` also \
+            matches /docs/{page}, /{lang}/docs with fewer."
+        ));
+
+        let syn_tree: syn::File = parse_quote! {
+            struct Params { lang: String, page: Option<u32> }
+            pub async fn docs(params: Option<Path<Params>>) {}
+        };
+        let err = check(&syn_tree, "/{lang?}/docs/{page?}")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("also matches /docs/{page}, without field(s): lang."));
+
+        let syn_tree: syn::File = parse_quote! {
+            struct Params { lang: Option<String>, page: Option<u32> }
+            pub async fn docs(params: Option<Path<Params>>) {}
+        };
+        assert!(check(&syn_tree, "/{lang?}/docs/{page?}")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_path_extractor_warns() -> Result<()> {
+        let syn_tree: syn::File = parse_quote! {
+            pub async fn get_user() {}
+        };
+
+        assert!(check(&syn_tree, "/users/{id}")?.is_some());
+        assert!(check(&syn_tree, "/users")?.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_tuple_arity() -> Result<()> {
+        let syn_tree: syn::File = parse_quote! {
+            pub async fn get_post(Path((user, post)): Path<(u32, u32)>) {}
+        };
+
+        assert!(check(&syn_tree, "/users/{user}/posts/{post}")?.is_none());
+        assert!(check(&syn_tree, "/users/{user}").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_scalar() -> Result<()> {
+        let syn_tree: syn::File = parse_quote! {
+            pub async fn get_user(Path(id): Path<u32>) {}
+        };
+
+        assert!(check(&syn_tree, "/users/{id}")?.is_none());
+        assert!(check(&syn_tree, "/users").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_struct_fields() -> Result<()> {
+        let syn_tree: syn::File = parse_quote! {
+            #[derive(Deserialize)]
+            pub struct PostParams {
+                user_id: u32,
+                #[serde(rename = "post")]
+                post_id: u32,
+                draft: Option<bool>,
+            }
+
+            pub async fn get_post(Path(params): Path<PostParams>) {}
+        };
+
+        assert!(check(&syn_tree, "/users/{user_id}/posts/{post}")?.is_none());
+        assert!(check(&syn_tree, "/users/{id}/posts/{post}").is_err());
+        Ok(())
+    }
+}
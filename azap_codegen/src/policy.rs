@@ -0,0 +1,352 @@
+use anyhow::{bail, Context, Result};
+
+use crate::{gaurds::parser::GuardStore, DiscoveredRoute, ROUTE_METHODS};
+
+/// Security rules checked against the discovered routes at build time.
+///
+/// Paths are matched as written in the route tree, without [`Config::base_path`], and a
+/// prefix only matches whole segments, so `/admin` covers `/admin/users` but not
+/// `/administrators`. Routes marked `#[public]` are exempt from every rule, but can't have
+/// a guard the policy requires anywhere, since that guard would make them not public.
+///
+/// Fallbacks, from `#[fallback]` or `_fallback.rs`, are checked at the path of their
+/// directory. Catchers and rejections apply to the whole router and aren't covered.
+///
+/// The policy is only set through [`Config::policy`], `azap.toml` isn't supported.
+///
+/// [`Config::base_path`]: crate::Config::base_path
+/// [`Config::policy`]: crate::Config::policy
+///
+/// # Examples
+///
+/// ```no_run
+/// use azap_codegen::{Config, Policy};
+///
+/// Config::new()
+///     .policy(Policy::new().require_guard("/admin", "auth").deny_unguarded())
+///     .generate();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Policy {
+    /// `(prefix, guard name)` pairs.
+    required: Vec<(String, String)>,
+    deny_unguarded: bool,
+}
+
+impl Policy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requires every route under `prefix` to have the guard named `guard`, e.g. `auth`
+    /// for `src/guards/auth.rs`. The name is resolved like in `#[guards(...)]`, so
+    /// `admin::auth` tells apart guards sharing a name.
+    pub fn require_guard(mut self, prefix: &str, guard: &str) -> Self {
+        let prefix = prefix.trim_end_matches('/');
+        self.required.push((
+            format!("/{}", prefix.trim_start_matches('/')),
+            guard.to_string(),
+        ));
+        self
+    }
+
+    /// Requires every route to have at least one guard.
+    pub fn deny_unguarded(mut self) -> Self {
+        self.deny_unguarded = true;
+        self
+    }
+
+    /// Fails with every violating handler at once, so a build lists them all.
+    pub(crate) fn check(&self, routes: &[DiscoveredRoute], guards: &GuardStore) -> Result<()> {
+        let required = self
+            .required
+            .iter()
+            .map(|(prefix, guard)| {
+                let reference: Vec<String> = guard.split("::").map(str::to_string).collect();
+                let resolved = guards
+                    .resolve(&reference)
+                    .with_context(|| format!("in the guard required under {}", prefix))?;
+                Ok((prefix, guard, resolved))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let mut violations = Vec::new();
+
+        for route in routes {
+            if route.public {
+                let required_guard = required.iter().find(|(_, _, resolved)| {
+                    resolved.is_some_and(|resolved| {
+                        route
+                            .guards
+                            .iter()
+                            .any(|g| g.module_path == resolved.module_path)
+                    })
+                });
+                if let Some((prefix, guard, _)) = required_guard {
+                    violations.push(format!(
+                        "  - `crate::{}::{}` ({} {}) is #[public] but has `{}`, required under {}",
+                        route.module_path,
+                        route.handler,
+                        route.method.to_uppercase(),
+                        route.full_path(),
+                        guard,
+                        prefix
+                    ));
+                }
+                continue;
+            }
+
+            if !is_covered(route) {
+                continue;
+            }
+
+            let path = route.full_path();
+            let mut missing = Vec::new();
+
+            if self.deny_unguarded && route.guards.is_empty() {
+                missing.push("has no guards".to_string());
+            }
+
+            for (prefix, guard, resolved) in &required {
+                if !is_under(&path, prefix) {
+                    continue;
+                }
+
+                let Some(resolved) = resolved else {
+                    bail!(
+                        "the security policy requires `{}` under {}, but there is no such guard.\n\
+                        Help: Name a guard from the guards directory or register_guards!, e.g. \
+                        `auth` for src/guards/auth.rs.",
+                        guard,
+                        prefix
+                    );
+                };
+
+                if route
+                    .guards
+                    .iter()
+                    .any(|g| g.module_path == resolved.module_path)
+                {
+                    continue;
+                }
+                missing.push(format!("is missing `{}`, required under {}", guard, prefix));
+            }
+
+            if !missing.is_empty() {
+                violations.push(format!(
+                    "  - `crate::{}::{}` ({} {}) {}",
+                    route.module_path,
+                    route.handler,
+                    route.method.to_uppercase(),
+                    path,
+                    missing.join(" and ")
+                ));
+            }
+        }
+
+        if !violations.is_empty() {
+            bail!(
+                "{} route(s) violate the security policy:\n{}\n\
+                Help: Add the guards with #[guards(...)], or mark routes that are meant to be \
+                open with #[public] and without the policy's guards.",
+                violations.len(),
+                violations.join("\n")
+            );
+        }
+
+        Ok(())
+    }
+}
+
+/// Routes, nested routers and services, and fallbacks.
+fn is_covered(route: &DiscoveredRoute) -> bool {
+    ROUTE_METHODS.contains(&route.method.as_str())
+        || route.is_nested()
+        || route.method == "fallback"
+}
+
+fn is_under(path: &str, prefix: &str) -> bool {
+    prefix == "/"
+        || path
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gaurds::parser::GuardStore;
+    use syn::parse_quote;
+
+    fn route(func: syn::ItemFn, module_path: &str, store: &GuardStore) -> DiscoveredRoute {
+        crate::extract_route_from_func(&func, module_path, store, false)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_is_under() {
+        assert!(is_under("/admin", "/admin"));
+        assert!(is_under("/admin/users", "/admin"));
+        assert!(!is_under("/administrators", "/admin"));
+        assert!(is_under("/health", "/"));
+    }
+
+    #[test]
+    fn test_check() -> Result<()> {
+        let store = GuardStore::from_source(&[(
+            "auth.rs",
+            "#[register_guard(guard_type = \"fn\")]\n\
+            pub async fn auth(req: Request, next: Next) -> Response { next.run(req).await }\n\
+            #[register_guard(guard_type = \"fn\")]\n\
+            pub async fn tracing(req: Request, next: Next) -> Response { next.run(req).await }\n",
+        )])?;
+
+        let guarded = route(
+            parse_quote! {
+                #[delete("/{id}")]
+                #[guards(auth)]
+                pub async fn delete_user() {}
+            },
+            "routes::admin::users::delete",
+            &store,
+        );
+        let traced = route(
+            parse_quote! {
+                #[get("/")]
+                #[guards(tracing)]
+                pub async fn list_users() {}
+            },
+            "routes::admin::users::get",
+            &store,
+        );
+        let login = route(
+            parse_quote! {
+                #[post("/login")]
+                #[public]
+                pub async fn login() {}
+            },
+            "routes::admin::login",
+            &store,
+        );
+        let logout = route(
+            parse_quote! {
+                #[post("/logout")]
+                #[public]
+                #[guards(auth)]
+                pub async fn logout() {}
+            },
+            "routes::admin::logout",
+            &store,
+        );
+        let status = route(
+            parse_quote! {
+                #[get("/")]
+                #[public]
+                #[guards(tracing)]
+                pub async fn status() {}
+            },
+            "routes::status",
+            &store,
+        );
+        let health = route(
+            parse_quote! {
+                #[get("/")]
+                pub async fn health() {}
+            },
+            "routes::health",
+            &store,
+        );
+        let not_found = route(
+            parse_quote! {
+                #[fallback]
+                pub async fn not_found() {}
+            },
+            "routes::admin::_fallback",
+            &store,
+        );
+
+        let policy = Policy::new().require_guard("/admin/", "auth");
+        assert!(policy
+            .check(
+                &[
+                    guarded.clone(),
+                    login.clone(),
+                    status.clone(),
+                    health.clone()
+                ],
+                &store
+            )
+            .is_ok());
+
+        let err = policy
+            .deny_unguarded()
+            .check(
+                &[guarded, traced, login, logout, status, health, not_found],
+                &store,
+            )
+            .unwrap_err()
+            .to_string();
+        assert!(err.starts_with("4 route(s) violate the security policy"));
+        assert!(err.contains(
+            "`crate::routes::admin::logout::logout` (POST /admin/logout) is #[public] but has \
+            `auth`, required under /admin"
+        ));
+        assert!(err.contains(
+            "`crate::routes::admin::users::get::list_users` (GET /admin/users) \
+            is missing `auth`, required under /admin"
+        ));
+        assert!(err.contains("`crate::routes::health::health` (GET /health) has no guards"));
+        assert!(err.contains(
+            "`crate::routes::admin::_fallback::not_found` (FALLBACK /admin) has no guards \
+            and is missing `auth`"
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_guard_paths() -> Result<()> {
+        let auth = "#[register_guard(guard_type = \"fn\")]\n\
+            pub async fn auth(req: Request, next: Next) -> Response { next.run(req).await }\n";
+        let store = GuardStore::from_source(&[("admin.rs", auth), ("api.rs", auth)])?;
+
+        let routes = [route(
+            parse_quote! {
+                #[get("/")]
+                #[guards(api::auth)]
+                pub async fn list_users() {}
+            },
+            "routes::admin::users::get",
+            &store,
+        )];
+
+        // Only the guard the policy names counts, not every guard sharing its name
+        let err = Policy::new()
+            .require_guard("/admin", "admin::auth")
+            .check(&routes, &store)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("is missing `admin::auth`, required under /admin"));
+        assert!(Policy::new()
+            .require_guard("/admin", "api::auth")
+            .check(&routes, &store)
+            .is_ok());
+
+        let err = format!(
+            "{:#}",
+            Policy::new()
+                .require_guard("/admin", "auth")
+                .check(&routes, &store)
+                .unwrap_err()
+        );
+        assert!(err.contains("guard `auth` is ambiguous"));
+
+        let err = Policy::new()
+            .require_guard("/admin", "auht")
+            .check(&routes, &store)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("requires `auht` under /admin, but there is no such guard"));
+        Ok(())
+    }
+}
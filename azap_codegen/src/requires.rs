@@ -0,0 +1,73 @@
+use syn::{
+    parse::{Parse, ParseStream},
+    Attribute, Ident, LitStr, Token,
+};
+
+/// Roles and scopes from `#[requires(role = "...", scope = "...")]`, enforced by
+/// `azap::authz::enforce`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Requirements {
+    pub roles: Vec<String>,
+    pub scopes: Vec<String>,
+}
+
+impl Requirements {
+    pub fn is_empty(&self) -> bool {
+        self.roles.is_empty() && self.scopes.is_empty()
+    }
+
+    /// Adds the requirements of one `#[requires(...)]`, a handler may have several.
+    pub fn extend_from_attr(&mut self, attr: &Attribute) -> syn::Result<()> {
+        let requirements = attr.parse_args::<Self>()?;
+        self.roles.extend(requirements.roles);
+        self.scopes.extend(requirements.scopes);
+        Ok(())
+    }
+}
+
+impl Parse for Requirements {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut requirements = Self::default();
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value = input.parse::<LitStr>()?.value();
+
+            match key.to_string().as_str() {
+                "role" => requirements.roles.push(value),
+                "scope" => requirements.scopes.push(value),
+                _ => return Err(syn::Error::new_spanned(key, "Expected 'role' or 'scope'")),
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        Ok(requirements)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_extend_from_attr() -> syn::Result<()> {
+        let mut requirements = Requirements::default();
+        requirements.extend_from_attr(&parse_quote!(#[requires(role = "admin")]))?;
+        requirements.extend_from_attr(&parse_quote!(#[requires(
+            scope = "users:read",
+            scope = "users:write",
+        )]))?;
+
+        assert_eq!(requirements.roles, ["admin"]);
+        assert_eq!(requirements.scopes, ["users:read", "users:write"]);
+        assert!(requirements
+            .extend_from_attr(&parse_quote!(#[requires(group = "staff")]))
+            .is_err());
+        Ok(())
+    }
+}
@@ -1,7 +1,11 @@
 // src/router.rs
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet};
 
-use crate::{gaurds::parser::GuardType, DiscoveredRoute};
+use crate::{
+    dirs::{Deprecation, Dir, Mount},
+    gaurds::parser::GuardType,
+    DiscoveredRoute, Root, TrailingSlash, ROUTE_METHODS,
+};
 
 #[derive(Debug, Clone)]
 pub(crate) struct Router(pub String);
@@ -11,90 +15,667 @@ impl Router {
         Self(String::new())
     }
 
-    pub fn generate(&mut self, routes: &[DiscoveredRoute]) {
+    pub fn generate(&mut self, routes: &[DiscoveredRoute], config: &crate::Config, root: &Root) {
         let code = &mut self.0;
 
+        // Catchers and the rejection handler wrap the whole router instead of being routed
+        let (layers, routes): (Vec<DiscoveredRoute>, Vec<DiscoveredRoute>) = routes
+            .iter()
+            .cloned()
+            .partition(|route| matches!(route.method.as_str(), "catch" | "rejection"));
+        let (catchers, rejections): (Vec<_>, Vec<_>) =
+            layers.iter().partition(|route| route.method == "catch");
+        let routes = &routes[..];
+
         // Header
         code.push_str("// Auto-generated by Azap - DO NOT EDIT\n");
-        code.push_str("// This file is generated from src/routes/ at build time\n\n");
-        code.push_str("use azap::Router;\n");
-        code.push_str("#[allow(unused_imports)]\n");
-        code.push_str("use azap::axum::routing::{get, post, put, patch, delete};\n");
-        code.push_str("use azap::axum::middleware;\n\n");
-
-        // Function signature
-        code.push_str(
-            "pub fn register_routes(state: AppState) -> Router\n\
-         {\n",
+        code.push_str(&format!(
+            "// This file is generated from src/{}/ at build time\n\n",
+            root.dir
+        ));
+
+        // The production router is built as is. `_with` takes guard overrides and only
+        // exists while `azap::testing` does. Imports stay inside the bodies so several
+        // generated files can be included in the same module.
+        code.push_str(&format!(
+            "pub fn {}(state: {}) -> azap::Router\n{{\n",
+            root.function, root.state
+        ));
+        push_register_body(code, routes, &catchers, &rejections, config, root, false);
+        code.push_str("}\n\n");
+
+        let mut with_overrides = format!(
+            "/// `{function}` with guards replaced or disabled, see `azap::testing::GuardOverrides`.\n\
+             pub fn {function}_with(\n\
+             \x20   state: {state},\n\
+             \x20   overrides: azap::testing::GuardOverrides<{state}>,\n\
+             ) -> azap::Router\n\
+             {{\n",
+            function = root.function,
+            state = root.state
+        );
+        push_register_body(
+            &mut with_overrides,
+            routes,
+            &catchers,
+            &rejections,
+            config,
+            root,
+            true,
         );
+        with_overrides.push_str("}\n");
+        code.push_str("azap::__testing! {\n");
+        code.push_str(&indent(&with_overrides));
+        code.push_str("}\n");
+
+        push_dir_routers(code, routes, config, root);
+    }
+}
+
+/// Emits the body of `register_routes`, or of `register_routes_with` when `overrides` is
+/// set.
+fn push_register_body(
+    code: &mut String,
+    routes: &[DiscoveredRoute],
+    catchers: &[&DiscoveredRoute],
+    rejections: &[&DiscoveredRoute],
+    config: &crate::Config,
+    root: &Root,
+    overrides: bool,
+) {
+    push_imports(code);
+    if overrides {
+        code.push_str("    let overrides = &overrides;\n\n");
+    }
+
+    // Routes in version directories are nested under their own router, and
+    // directories with a fallback are nested from their directory router
+    let fallback_dirs = fallback_dirs(routes);
+    let mut mounts: BTreeMap<&str, (&Mount, Scope)> = BTreeMap::new();
+    let mut top_level = Scope::default();
+
+    for route in routes {
+        let scope = match &route.mount {
+            Some(mount) => {
+                &mut mounts
+                    .entry(mount.path.as_str())
+                    .or_insert_with(|| (mount, Scope::default()))
+                    .1
+            }
+            None => &mut top_level,
+        };
+
+        match fallback_dirs.iter().find(|dir| dir.contains(route)) {
+            Some(dir) => {
+                let nested = scope
+                    .nested
+                    .entry(&dir.path)
+                    .or_insert_with(|| (dir.router(root), false));
+                nested.1 |= serves(route, &dir.path);
+            }
+            None => scope.routes.push(route),
+        }
+    }
+
+    code.push_str("    let mut router = Router::new();\n\n");
+    top_level.push(code, "router", config, overrides);
+    let serves_root = top_level.routes.iter().any(|route| serves(route, "/"));
+
+    for (index, (path, (mount, scope))) in mounts.into_iter().enumerate() {
+        let var = format!("mount_{}", index);
+
+        code.push_str(&format!("    // Routes nested under {}\n", path));
+        code.push_str(&format!("    let mut {} = Router::new();\n\n", var));
+        scope.push(code, &var, config, overrides);
+
+        if let Some(deprecation) = &mount.deprecation {
+            push_deprecation(code, &var, deprecation);
+        }
+
+        code.push_str(&format!(
+            "    router = router.nest(\"{}\", {});\n",
+            path, var
+        ));
+        if scope.routes.iter().any(|route| serves(route, "/"))
+            && let Some(redirect) = nest_redirect(path, config)
+        {
+            code.push_str(&format!("    router = router{};\n", redirect));
+        }
+        code.push('\n');
+    }
+
+    if let Some(base_path) = &config.base_path {
+        code.push_str(&format!(
+            "    let router = Router::new().nest(\"{}\", router);\n",
+            base_path
+        ));
+        if serves_root && let Some(redirect) = nest_redirect(base_path, config) {
+            code.push_str(&format!("    let router = router{};\n", redirect));
+        }
+        code.push('\n');
+    }
+
+    for rejection in rejections {
+        code.push_str(&format!(
+            "    let router = router.layer(azap::axum::Extension(\
+            azap::extract::Rejections(crate::{}::__azap_rejection_{})));\n\n",
+            rejection.module_path, rejection.handler
+        ));
+    }
+
+    if !catchers.is_empty() {
+        code.push_str("    // Catchers\n");
+        code.push_str("    let router = router.layer(middleware::from_fn_with_state(\n");
+        code.push_str("        azap::catch::Catchers::new()\n");
+        for catcher in catchers {
+            code.push_str(&format!(
+                "            .on({}, crate::{}::__azap_catch_{})\n",
+                catcher.path, catcher.module_path, catcher.handler
+            ));
+        }
+        code.push_str("        ,\n");
+        code.push_str("        azap::catch::catch,\n");
+        code.push_str("    ));\n\n");
+    }
+
+    if overrides {
+        code.push_str("    overrides.finish();\n");
+    }
+    code.push_str("    router.with_state(state)\n");
+}
+
+/// Routes generated into one router of `register_routes`.
+#[derive(Default)]
+struct Scope<'a> {
+    routes: Vec<&'a DiscoveredRoute>,
+    /// Directory routers to nest, by URL path, and whether they serve their own `/`.
+    nested: BTreeMap<&'a str, (String, bool)>,
+}
+
+impl Scope<'_> {
+    fn push(&self, code: &mut String, var: &str, config: &crate::Config, overrides: bool) {
+        push_groups(code, var, &self.routes, config, overrides);
 
-        // Group routes by their unique middleware combination
-        let mut route_groups: HashMap<String, Vec<&DiscoveredRoute>> = HashMap::new();
+        for (path, (router, serves_root)) in &self.nested {
+            let call = match overrides {
+                true => format!("{}_with(&state, overrides)", router),
+                false => format!("{}(&state)", router),
+            };
+            code.push_str(&format!(
+                "    {var} = {var}.nest(\"{}\", {});\n",
+                path,
+                call,
+                var = var
+            ));
+            if *serves_root && let Some(redirect) = nest_redirect(path, config) {
+                code.push_str(&format!("    {var} = {var}{};\n", redirect, var = var));
+            }
+        }
+
+        if !self.nested.is_empty() {
+            code.push('\n');
+        }
+    }
+}
+
+/// A directory below the root of its router that has its own fallback.
+struct FallbackDir {
+    modules: Vec<String>,
+    /// URL path relative to the router the directory is nested in.
+    path: String,
+}
 
-        for route in routes {
-            // Create a unique key based on the route's guards
-            let guard_key = route
-                .guards
+impl FallbackDir {
+    fn contains(&self, route: &DiscoveredRoute) -> bool {
+        route.dirs.len() >= self.modules.len()
+            && route
+                .dirs
                 .iter()
-                .map(|g| format!("{}:{}", &g.guard_type, &g.module_path))
-                .collect::<Vec<_>>()
-                .join("|");
+                .zip(&self.modules)
+                .all(|(dir, module)| &dir.module == module)
+    }
+
+    /// Path of the directory's generated router function.
+    fn router(&self, root: &Root) -> String {
+        format!("{}::{}::router", root.module(), self.modules.join("::"))
+    }
+}
 
-            route_groups.entry(guard_key).or_default().push(route);
+/// Returns the outermost directories with a fallback that can't be set with
+/// `.fallback()` on `register_routes` or a version router. Axum can't mix a catch-all
+/// route with `{param}` routes, so these are nested instead.
+fn fallback_dirs(routes: &[DiscoveredRoute]) -> Vec<FallbackDir> {
+    let mut dirs: Vec<FallbackDir> = Vec::new();
+
+    for route in routes.iter().filter(|route| route.method == "fallback") {
+        let skip = usize::from(route.mount.is_some());
+        // Directories mounted on their parent with `path = ""` share its fallback scope
+        let Some(last) = route.dirs[skip..]
+            .iter()
+            .rposition(|dir| !dir.segment.is_empty())
+        else {
+            continue;
+        };
+
+        dirs.push(FallbackDir {
+            modules: route.dirs[..=skip + last]
+                .iter()
+                .map(|dir| dir.module.clone())
+                .collect(),
+            path: route.dirs[skip..=skip + last]
+                .iter()
+                .filter(|dir| !dir.segment.is_empty())
+                .map(|dir| format!("/{}", dir.segment))
+                .collect(),
+        });
+    }
+
+    dirs.sort_by_key(|dir| dir.modules.len());
+
+    let mut outermost: Vec<FallbackDir> = Vec::new();
+    for dir in dirs {
+        if !outermost
+            .iter()
+            .any(|outer| dir.modules.starts_with(&outer.modules))
+        {
+            outermost.push(dir);
         }
+    }
 
-        code.push_str("    let mut router = Router::new();\n\n");
+    outermost
+}
+
+/// Emits a module per directory with a `router(&state)` function nesting the routers
+/// of its subdirectories, so a subtree can be mounted or tested on its own.
+/// `router_with(&state, &overrides)` builds it with guard overrides, and like
+/// `register_routes_with` only exists while `azap::testing` does.
+///
+/// A directory's own `/` route is served at its prefix, e.g. `/users`, and `/users/` is
+/// routed on the parent with [`nest_redirect`].
+fn push_dir_routers(
+    code: &mut String,
+    routes: &[DiscoveredRoute],
+    config: &crate::Config,
+    root: &Root,
+) {
+    // Paths are relative to the directory router they end up in
+    let relative: Vec<DiscoveredRoute> = routes
+        .iter()
+        .map(|route| DiscoveredRoute {
+            base_path: String::new(),
+            mount: None,
+            ..route.clone()
+        })
+        .collect();
+    let relative: Vec<&DiscoveredRoute> = relative.iter().collect();
 
-        // Generate a separate sub-router for each middleware combination
-        for (guard_key, group_routes) in route_groups {
-            if group_routes.is_empty() {
+    code.push_str(&format!(
+        "\n/// Routers for each directory under src/{}/, e.g. `{}::users::router(&state)`.\n",
+        root.dir,
+        root.module()
+    ));
+    code.push_str("#[allow(dead_code)]\n");
+    code.push_str(&format!("pub mod {} {{\n", root.module()));
+    code.push_str(&indent(&dir_router(&[], &relative, config, root)));
+    code.push_str("}\n");
+}
+
+/// Generates the contents of the module for the directory at `path`. `routes` holds
+/// every route below it.
+fn dir_router(
+    path: &[&Dir],
+    routes: &[&DiscoveredRoute],
+    config: &crate::Config,
+    root: &Root,
+) -> String {
+    let depth = path.len();
+    let own: Vec<&DiscoveredRoute> = routes
+        .iter()
+        .copied()
+        .filter(|route| route.dirs.len() == depth)
+        .collect();
+    let mut children: BTreeMap<&str, (&Dir, Vec<&DiscoveredRoute>)> = BTreeMap::new();
+
+    for route in routes {
+        if let Some(dir) = route.dirs.get(depth) {
+            children
+                .entry(dir.module.as_str())
+                .or_insert_with(|| (dir, Vec::new()))
+                .1
+                .push(route);
+        }
+    }
+
+    let mut code = String::new();
+
+    code.push_str("#[allow(unused_imports)]\n");
+    code.push_str("use super::*;\n\n");
+    code.push_str("#[allow(unused_variables)]\n");
+    code.push_str(&format!(
+        "pub fn router(state: &{state}) -> azap::Router<{state}> {{\n",
+        state = root.state
+    ));
+    push_dir_router_body(&mut code, path, &own, &children, config, false);
+    code.push_str("}\n\n");
+
+    let mut with_overrides = format!(
+        "#[allow(unused_variables)]\n\
+        pub fn router_with(\n\
+        \x20   state: &{state},\n\
+        \x20   overrides: &azap::testing::GuardOverrides<{state}>,\n\
+        ) -> azap::Router<{state}> {{\n",
+        state = root.state
+    );
+    push_dir_router_body(&mut with_overrides, path, &own, &children, config, true);
+    with_overrides.push_str("}\n");
+    code.push_str("azap::__testing! {\n");
+    code.push_str(&indent(&with_overrides));
+    code.push_str("}\n");
+
+    for (module, (dir, child_routes)) in &children {
+        let mut child_path = path.to_vec();
+        child_path.push(dir);
+
+        code.push_str(&format!("\npub mod {} {{\n", module));
+        code.push_str(&indent(&dir_router(
+            &child_path,
+            child_routes,
+            config,
+            root,
+        )));
+        code.push_str("}\n");
+    }
+
+    code
+}
+
+/// Emits the body of a directory's `router`, or of its `router_with` when `overrides` is
+/// set.
+fn push_dir_router_body(
+    code: &mut String,
+    path: &[&Dir],
+    own: &[&DiscoveredRoute],
+    children: &BTreeMap<&str, (&Dir, Vec<&DiscoveredRoute>)>,
+    config: &crate::Config,
+    overrides: bool,
+) {
+    push_imports(code);
+    code.push_str("    let mut router = Router::new();\n\n");
+    push_groups(code, "router", own, config, overrides);
+
+    for (module, (dir, child_routes)) in children {
+        let call = match overrides {
+            true => format!("{}::router_with(state, overrides)", module),
+            false => format!("{}::router(state)", module),
+        };
+        match dir.segment.as_str() {
+            "" => code.push_str(&format!("    router = router.merge({});\n", call)),
+            segment => {
+                code.push_str(&format!(
+                    "    router = router.nest(\"/{}\", {});\n",
+                    segment, call
+                ));
+
+                // Routes of the child and of the directories merged into it
+                let serves_root = child_routes.iter().any(|route| {
+                    route.dirs[path.len() + 1..]
+                        .iter()
+                        .all(|dir| dir.segment.is_empty())
+                        && serves(route, "/")
+                });
+                if serves_root
+                    && let Some(redirect) = nest_redirect(&format!("/{}", segment), config)
+                {
+                    code.push_str(&format!("    router = router{};\n", redirect));
+                }
+            }
+        }
+    }
+
+    if let Some(deprecation) = path.last().and_then(|dir| dir.deprecation.as_ref()) {
+        push_deprecation(code, "router", deprecation);
+    }
+
+    if !children.is_empty() {
+        code.push('\n');
+    }
+
+    code.push_str("    router\n");
+}
+
+/// Imports shared by every generated router function.
+fn push_imports(code: &mut String) {
+    code.push_str("    use azap::Router;\n");
+    code.push_str("    #[allow(unused_imports)]\n");
+    code.push_str("    use azap::axum::routing::{get, post, put, patch, delete};\n");
+    code.push_str("    #[allow(unused_imports)]\n");
+    code.push_str("    use azap::axum::middleware;\n\n");
+}
+
+fn push_deprecation(code: &mut String, var: &str, deprecation: &Deprecation) {
+    code.push_str(&format!(
+        "    {var} = {var}.layer(middleware::from_fn_with_state(\
+        azap::deprecation::Deprecation::new({:?}, {:?}, {:?}), \
+        azap::deprecation::headers));\n",
+        deprecation.deprecation,
+        deprecation.sunset,
+        deprecation.link,
+        var = var
+    ));
+}
+
+fn indent(code: &str) -> String {
+    code.lines()
+        .map(|line| match line {
+            "" => "\n".to_string(),
+            line => format!("    {}\n", line),
+        })
+        .collect()
+}
+
+/// Every path a handler is served at, after expanding optional segments.
+fn route_paths(route: &DiscoveredRoute) -> Vec<String> {
+    expand_optional_segments(&combine_paths(&route.base_path, &route.path))
+        .into_iter()
+        .map(|path| match path.is_empty() {
+            true => "/".to_string(),
+            false => path,
+        })
+        .collect()
+}
+
+/// Whether `route` is a handler served at `path` of the router it's generated in.
+fn serves(route: &DiscoveredRoute, path: &str) -> bool {
+    ROUTE_METHODS.contains(&route.method.as_str())
+        && route_paths(route).iter().any(|served| served == path)
+}
+
+/// `nest` serves the `/` of a nested router at `prefix` only, so unless the policy is
+/// strict the parent routes `{prefix}/` to a redirect with this `.route(...)` call.
+fn nest_redirect(prefix: &str, config: &crate::Config) -> Option<String> {
+    match config.trailing_slash {
+        TrailingSlash::Strict => None,
+        _ => Some(format!(
+            ".route(\"{}/\", azap::axum::routing::any(azap::trailing_slash::redirect))",
+            prefix
+        )),
+    }
+}
+
+/// Emits one sub-router per guard combination and merges them into `var`.
+fn push_groups(
+    code: &mut String,
+    var: &str,
+    routes: &[&DiscoveredRoute],
+    config: &crate::Config,
+    overrides: bool,
+) {
+    let canonical_paths: BTreeSet<String> = routes
+        .iter()
+        .filter(|route| route.method != "fallback")
+        .flat_map(|route| route_paths(route))
+        .collect();
+    let alternate_path = |path: &str| {
+        toggle_trailing_slash(path).filter(|alternate| !canonical_paths.contains(alternate))
+    };
+    let mut redirects: BTreeSet<String> = BTreeSet::new();
+
+    // Group routes by their unique middleware combination
+    let mut route_groups: BTreeMap<String, Vec<&DiscoveredRoute>> = BTreeMap::new();
+
+    for route in routes {
+        // Create a unique key based on the route's guards
+        let guard_key = route
+            .guards
+            .iter()
+            .map(|g| format!("{}:{}", &g.guard_type, &g.module_path))
+            .collect::<Vec<_>>()
+            .join("|");
+
+        route_groups.entry(guard_key).or_default().push(route);
+    }
+
+    // Generate a separate sub-router for each middleware combination
+    for (guard_key, group_routes) in route_groups {
+        if group_routes.is_empty() {
+            continue;
+        }
+
+        code.push_str("    // Group with middleware: ");
+        if guard_key.is_empty() {
+            code.push_str("none\n");
+        } else {
+            code.push_str(&format!("{}\n", guard_key.replace('|', ", ")));
+        }
+
+        code.push_str("    let group = Router::new()\n");
+
+        // Add all routes in this group
+        for route in &group_routes {
+            let handler_path = format!("crate::{}::{}", route.module_path, route.handler);
+
+            // Fallbacks below the root of a router are nested with their directory, so
+            // the ones left here belong to the router itself.
+            if route.method == "fallback" {
+                code.push_str(&format!("        .fallback({})\n", handler_path));
                 continue;
             }
 
-            code.push_str("    // Group with middleware: ");
-            if guard_key.is_empty() {
-                code.push_str("none\n");
-            } else {
-                code.push_str(&format!("{}\n", guard_key.replace('|', ", ")));
+            if route.is_nested() {
+                let call = match route.method.as_str() {
+                    "mount" => "nest",
+                    _ => "nest_service",
+                };
+
+                for full_path in route_paths(route) {
+                    code.push_str(&format!(
+                        "        .{}(\"{}\", {}())\n",
+                        call, full_path, handler_path
+                    ));
+                }
+                continue;
             }
 
-            code.push_str("    let group = Router::new()\n");
+            let mut method_router = format!("{}({})", route.method, handler_path);
 
-            // Add all routes in this group
-            for route in &group_routes {
-                let base_path = extract_base_path(&route.module_path);
-                let full_path = combine_paths(&base_path, &route.path);
-                let handler_path = format!("crate::{}::{}", route.module_path, route.handler);
+            // Inside the constraints, so a path that doesn't match is still a `404`
+            if !route.requirements.is_empty() {
+                method_router.push_str(&format!(
+                    ".route_layer(middleware::from_fn_with_state(\
+                    azap::authz::Requirements::new(&{:?}, &{:?}), \
+                    azap::authz::enforce))",
+                    route.requirements.roles, route.requirements.scopes
+                ));
+            }
 
-                code.push_str(&format!(
-                    "        .route(\"{}\", {}({}))\n",
-                    full_path, route.method, handler_path
+            if !route.constraints.is_empty() {
+                let constraints = route
+                    .constraints
+                    .iter()
+                    .map(|c| format!("({:?}, {:?})", c.name, c.constraint))
+                    .collect::<Vec<_>>()
+                    .join(", ");
+
+                method_router.push_str(&format!(
+                    ".route_layer(middleware::from_fn_with_state(\
+                    azap::constraints::PathConstraints::new(&[{}]), \
+                    azap::constraints::enforce))",
+                    constraints
                 ));
             }
 
-            // Apply middleware layers for this group
-            if !group_routes[0].guards.is_empty() {
-                for guard in &group_routes[0].guards {
-                    match guard.guard_type {
-                    GuardType::FromFn => code.push_str(&format!(
-                        "        .layer(middleware::from_fn(crate::{}))\n",
-                        guard.module_path
-                    )),
-                    GuardType::FromFnWithState => code.push_str(&format!(
-                        "        .layer(middleware::from_fn_with_state(state.clone(), crate::{}))\n",
-                        guard.module_path
+            for full_path in route_paths(route) {
+                code.push_str(&format!(
+                    "        .route(\"{}\", {})\n",
+                    full_path, method_router
+                ));
+
+                let Some(alternate) = alternate_path(&full_path) else {
+                    continue;
+                };
+
+                match config.trailing_slash {
+                    TrailingSlash::Strict => {}
+                    TrailingSlash::Redirect => {
+                        redirects.insert(alternate);
+                    }
+                    TrailingSlash::Both => code.push_str(&format!(
+                        "        .route(\"{}\", {})\n",
+                        alternate, method_router
                     )),
-                    _ => (),
                 }
+            }
+        }
+
+        code.push_str("    ;\n");
+
+        // Apply middleware layers for this group, unless overridden in tests
+        for guard in &group_routes[0].guards {
+            let layer = match guard.guard_type {
+                GuardType::FromFn => format!("middleware::from_fn({})", guard.expr()),
+                GuardType::FromFnWithState => format!(
+                    "middleware::from_fn_with_state(state.clone(), {})",
+                    guard.expr()
+                ),
+                GuardType::Authenticate => format!(
+                    "azap::guard::layer(azap::guarded::authenticate({}), state.clone())",
+                    guard.expr()
+                ),
+                GuardType::Layer => guard.expr(),
+                GuardType::Const => {
+                    format!("azap::guard::layer({}, state.clone())", guard.expr())
                 }
+                GuardType::Impl => format!(
+                    "azap::guard::from_state::<{}, _>(state.clone())",
+                    guard.expr()
+                ),
+            };
+
+            match overrides {
+                true => code.push_str(&format!(
+                    "    let group = overrides.apply(\"{}\", group, |group| group.layer({}));\n",
+                    guard.override_path(),
+                    layer
+                )),
+                false => code.push_str(&format!("    let group = group.layer({});\n", layer)),
             }
+        }
+
+        code.push_str(&format!("    {var} = {var}.merge(group);\n\n", var = var));
+    }
+
+    if !redirects.is_empty() {
+        code.push_str("    // Trailing slash redirects\n");
+        code.push_str(&format!("    {} = {}\n", var, var));
 
-            code.push_str("    ;\n");
-            code.push_str("    router = router.merge(group);\n\n");
+        for path in &redirects {
+            code.push_str(&format!(
+                "        .route(\"{}\", azap::axum::routing::any(azap::trailing_slash::redirect))\n",
+                path
+            ));
         }
 
-        code.push_str("    router.with_state(state)\n");
-        code.push_str("}\n");
+        code.push_str("    ;\n\n");
     }
 }
 
@@ -112,7 +693,8 @@ impl Default for Router {
 /// ```
 ///
 /// Rules:
-/// - If there are exactly 2 segments, returns `"/<resource>"`
+/// - If there are exactly 2 segments, returns `"/<resource>"`, or `/` for
+///   `routes::_fallback`
 /// - If there are more than 2 segments, joins the middle segments with `/`
 /// - Otherwise, returns `/`
 ///
@@ -126,11 +708,13 @@ impl Default for Router {
 ///     extract_base_path("routes::users::admin::get")
 /// );
 /// assert_eq!("/auth", extract_base_path("routes::auth"));
+/// assert_eq!("/", extract_base_path("routes::_fallback"));
 /// ```
-fn extract_base_path(module_path: &str) -> String {
+pub(crate) fn extract_base_path(module_path: &str) -> String {
     let parts: Vec<&str> = module_path.split("::").collect();
 
     match parts.len() {
+        2 if parts[1] == crate::FALLBACK_MODULE => "/".into(),
         2 => format!("/{}", parts[1]),
         n if n > 2 => format!("/{}", parts[1..parts.len() - 1].join("/")),
         _ => "/".into(),
@@ -141,22 +725,92 @@ fn extract_base_path(module_path: &str) -> String {
 ///
 /// Rules:
 /// - If `path` is `/`, the base path is returned unchanged
-/// - If `path` starts with `/`, it is appended directly
-/// - Otherwise, a `/` is inserted between `base` and `path`
+/// - Otherwise, `base` and `path` are joined with exactly one `/`
 ///
 /// # Examples
 ///
 /// ```no__run
 /// # use crate::combine_paths;
 /// assert_eq!(combine_paths("/users", "/"), "/users");
-/// assert_eq!(combine_paths("/users", "/:id"), "/users/:id");
+/// assert_eq!(combine_paths("/users", "/{id}"), "/users/{id}");
 /// assert_eq!(combine_paths("/auth", "login"), "/auth/login");
+/// assert_eq!(combine_paths("/", "/static"), "/static");
 /// ```
-fn combine_paths(base: &str, path: &str) -> String {
+pub(crate) fn combine_paths(base: &str, path: &str) -> String {
     match path {
         "/" => base.to_string(),
-        p if p.starts_with('/') => format!("{}{}", base, p),
-        p => format!("{}/{}", base, p),
+        p => format!(
+            "{}/{}",
+            base.trim_end_matches('/'),
+            p.trim_start_matches('/')
+        ),
+    }
+}
+
+/// Expands `{param?}` segments into every path they allow, longest first.
+///
+/// # Examples
+///
+/// ```no__run
+/// # use crate::expand_optional_segments;
+/// assert_eq!(
+///     expand_optional_segments("/{lang?}/docs"),
+///     vec!["/{lang}/docs", "/docs"]
+/// );
+/// ```
+pub(crate) fn expand_optional_segments(path: &str) -> Vec<String> {
+    let mut paths: Vec<String> = vec![String::new()];
+
+    for segment in path.split('/').filter(|segment| !segment.is_empty()) {
+        match segment.strip_prefix('{').and_then(|s| s.strip_suffix("?}")) {
+            Some(param) => {
+                let without = paths.clone();
+                for path in &mut paths {
+                    path.push_str(&format!("/{{{}}}", param));
+                }
+                paths.extend(without);
+            }
+            None => {
+                for path in &mut paths {
+                    path.push('/');
+                    path.push_str(segment);
+                }
+            }
+        }
+    }
+
+    let trailing_slash = path.len() > 1 && path.ends_with('/');
+
+    paths
+        .into_iter()
+        .map(|mut path| {
+            if path.is_empty() || trailing_slash {
+                path.push('/');
+            }
+            path
+        })
+        .collect()
+}
+
+/// Returns the other spelling of `path` with respect to a trailing slash, or `None` for
+/// `/` and wildcard routes, which have no alternate.
+///
+/// # Examples
+///
+/// ```no__run
+/// # use crate::toggle_trailing_slash;
+/// assert_eq!(toggle_trailing_slash("/users"), Some("/users/".to_string()));
+/// assert_eq!(toggle_trailing_slash("/users/"), Some("/users".to_string()));
+/// assert_eq!(toggle_trailing_slash("/"), None);
+/// ```
+fn toggle_trailing_slash(path: &str) -> Option<String> {
+    if path == "/" || path.rsplit('/').next().is_some_and(|s| s.starts_with("{*")) {
+        return None;
+    }
+
+    match path.strip_suffix('/') {
+        Some(stripped) => Some(stripped.to_string()),
+        None => Some(format!("{}/", path)),
     }
 }
 
@@ -177,7 +831,366 @@ mod tests {
     #[test]
     fn test_combine_paths() {
         assert_eq!(combine_paths("/users", "/"), "/users");
-        assert_eq!(combine_paths("/users", "/:id"), "/users/:id");
+        assert_eq!(combine_paths("/users", "/{id}"), "/users/{id}");
         assert_eq!(combine_paths("/auth", "login"), "/auth/login");
+        assert_eq!(combine_paths("/", "/static"), "/static");
+    }
+
+    #[test]
+    fn test_expand_optional_segments() {
+        assert_eq!(expand_optional_segments("/users"), ["/users"]);
+        assert_eq!(expand_optional_segments("/"), ["/"]);
+        assert_eq!(
+            expand_optional_segments("/{lang?}/docs"),
+            ["/{lang}/docs", "/docs"]
+        );
+        assert_eq!(
+            expand_optional_segments("/docs/{lang?}/{page?}"),
+            [
+                "/docs/{lang}/{page}",
+                "/docs/{page}",
+                "/docs/{lang}",
+                "/docs"
+            ]
+        );
+        assert_eq!(expand_optional_segments("/{lang?}"), ["/{lang}", "/"]);
+    }
+
+    #[test]
+    fn test_toggle_trailing_slash() {
+        assert_eq!(toggle_trailing_slash("/users"), Some("/users/".to_string()));
+        assert_eq!(toggle_trailing_slash("/users/"), Some("/users".to_string()));
+        assert_eq!(toggle_trailing_slash("/"), None);
+        assert_eq!(toggle_trailing_slash("/assets/{*path}"), None);
+    }
+
+    fn route(module_path: &str, path: &str) -> DiscoveredRoute {
+        DiscoveredRoute {
+            method: "get".to_string(),
+            path: path.to_string(),
+            handler: "handler".to_string(),
+            module_path: module_path.to_string(),
+            base_path: extract_base_path(module_path),
+            mount: None,
+            dirs: Vec::new(),
+            guards: Vec::new(),
+            constraints: Vec::new(),
+            requirements: Default::default(),
+            public: false,
+        }
+    }
+
+    #[test]
+    fn test_generate_trailing_slash() {
+        let routes = [
+            route("routes::users::get", "/"),
+            route("routes::docs", "/{lang?}/"),
+        ];
+
+        let generate = |policy| {
+            let mut router = Router::new();
+            router.generate(
+                &routes,
+                &crate::Config::new().trailing_slash(policy),
+                &Root::default(),
+            );
+            router.0
+        };
+
+        let strict = generate(TrailingSlash::Strict);
+        assert!(strict.contains(r#".route("/users", get("#));
+        assert!(strict.contains(r#".route("/docs/{lang}/", get("#));
+        assert!(strict.contains(r#".route("/docs/", get("#));
+        assert!(!strict.contains(r#".route("/users/""#));
+
+        let redirect = generate(TrailingSlash::Redirect);
+        assert!(redirect.contains(r#".route("/users/", azap::axum::routing::any("#));
+        assert!(redirect.contains(r#".route("/docs", azap::axum::routing::any("#));
+
+        let both = generate(TrailingSlash::Both);
+        assert!(both.contains(r#".route("/users/", get("#));
+        assert!(both.contains(r#".route("/docs/{lang}", get("#));
+    }
+
+    #[test]
+    fn test_generate_nested_trailing_slash() {
+        let dir = |module: &str, segment: &str| Dir {
+            module: module.to_string(),
+            segment: segment.to_string(),
+            top_level: true,
+            deprecation: None,
+        };
+
+        let mut index = route("routes::index", "/");
+        index.base_path = String::new();
+        let mut users = route("routes::users::get", "/");
+        users.dirs = vec![dir("users", "users")];
+        let mut v1 = route("routes::v1::get", "/");
+        v1.base_path = String::new();
+        v1.dirs = vec![dir("v1", "v1")];
+        v1.mount = Some(Mount {
+            path: "/v1".to_string(),
+            deprecation: None,
+        });
+        let routes = [index, users, v1];
+
+        let generate = |policy| {
+            let mut router = Router::new();
+            router.generate(
+                &routes,
+                &crate::Config::new()
+                    .base_path("/api")
+                    .trailing_slash(policy),
+                &Root::default(),
+            );
+            router.0
+        };
+
+        // `/api/`, `/api/v1/` and the `/users/` of the users directory router are
+        // routed where their router is nested
+        let redirect = generate(TrailingSlash::Redirect);
+        assert!(redirect.contains(
+            "    router = router.route(\"/v1/\", azap::axum::routing::any(azap::trailing_slash::redirect));"
+        ));
+        assert!(redirect.contains(
+            "    let router = router.route(\"/api/\", azap::axum::routing::any(azap::trailing_slash::redirect));"
+        ));
+        assert!(redirect.contains(
+            "    router = router.route(\"/users/\", azap::axum::routing::any(azap::trailing_slash::redirect));"
+        ));
+        assert!(generate(TrailingSlash::Both).contains(r#"router.route("/v1/", "#));
+
+        let strict = generate(TrailingSlash::Strict);
+        assert!(!strict.contains(r#".route("/v1/""#));
+        assert!(!strict.contains(r#".route("/api/""#));
+    }
+
+    #[test]
+    fn test_generate_mounts() {
+        let mut v1 = route("routes::v1::users::get", "/");
+        v1.base_path = "/users".to_string();
+        v1.mount = Some(Mount {
+            path: "/v1".to_string(),
+            deprecation: Some(crate::dirs::Deprecation {
+                deprecation: Some("@1751241600".to_string()),
+                sunset: Some("Wed, 31 Dec 2025 23:59:59 GMT".to_string()),
+                link: None,
+            }),
+        });
+        let routes = [route("routes::health", "/"), v1];
+
+        let mut router = Router::new();
+        router.generate(
+            &routes,
+            &crate::Config::new().base_path("/api"),
+            &Root::default(),
+        );
+        let code = router.0;
+
+        assert!(code.contains(r#".route("/health", get("#));
+        assert!(code.contains(r#".route("/users", get(crate::routes::v1::users::get::handler))"#));
+        assert!(code.contains(
+            r#"azap::deprecation::Deprecation::new(Some("@1751241600"), Some("Wed, 31 Dec 2025 23:59:59 GMT"), None)"#
+        ));
+        assert!(code.contains(r#"router = router.nest("/v1", mount_0);"#));
+        assert!(code.contains(r#"let router = Router::new().nest("/api", router);"#));
+    }
+
+    #[test]
+    fn test_generate_dir_routers() {
+        let dir = |module: &str, segment: &str, top_level| Dir {
+            module: module.to_string(),
+            segment: segment.to_string(),
+            top_level,
+            deprecation: None,
+        };
+
+        let mut list = route("routes::user_profiles::get", "/");
+        list.dirs = vec![dir("user_profiles", "user-profiles", true)];
+        let mut avatar = route("routes::user_profiles::avatar::get", "/{size}");
+        avatar.dirs = vec![
+            dir("user_profiles", "user-profiles", true),
+            dir("avatar", "", false),
+        ];
+
+        let mut router = Router::new();
+        router.generate(&[list, avatar], &crate::Config::new(), &Root::default());
+        let code = router.0;
+
+        assert!(code.contains("pub mod __azap {"));
+        assert!(code.contains(
+            r#"router = router.nest("/user-profiles", user_profiles::router_with(state, overrides));"#
+        ));
+        assert!(code.contains("pub mod user_profiles {"));
+        assert!(code.contains(r#".route("/", get(crate::routes::user_profiles::get::handler))"#));
+        assert!(code.contains("router = router.merge(avatar::router_with(state, overrides));"));
+        assert!(code.contains(
+            r#".route("/{size}", get(crate::routes::user_profiles::avatar::get::handler))"#
+        ));
+    }
+
+    #[test]
+    fn test_generate_nested() {
+        let mut legacy = route("routes::users::legacy", "/legacy");
+        legacy.method = "mount".to_string();
+        legacy.base_path = "/users".to_string();
+        let mut assets = route("routes::assets", "/static");
+        assets.method = "service".to_string();
+        assets.base_path = "/".to_string();
+
+        let mut router = Router::new();
+        router.generate(
+            &[legacy, assets],
+            &crate::Config::new().trailing_slash(TrailingSlash::Both),
+            &Root::default(),
+        );
+        let code = router.0;
+
+        assert!(code.contains(r#".nest("/users/legacy", crate::routes::users::legacy::handler())"#));
+        assert!(code.contains(r#".nest_service("/static", crate::routes::assets::handler())"#));
+        assert!(!code.contains("/legacy/"));
+    }
+
+    #[test]
+    fn test_generate_fallbacks() {
+        let api = Dir {
+            module: "api".to_string(),
+            segment: "api".to_string(),
+            top_level: true,
+            deprecation: None,
+        };
+        let fallback = |module_path: &str, dirs: &[Dir]| {
+            let mut route = route(module_path, "/");
+            route.method = "fallback".to_string();
+            route.dirs = dirs.to_vec();
+            route
+        };
+        let mut list = route("routes::api::get", "/{id}");
+        list.dirs = vec![api.clone()];
+        let routes = [
+            list,
+            fallback("routes::_fallback", &[]),
+            fallback("routes::api::_fallback", &[api]),
+        ];
+
+        let mut router = Router::new();
+        router.generate(&routes, &crate::Config::new(), &Root::default());
+        let code = router.0;
+
+        assert!(code.contains(".fallback(crate::routes::_fallback::handler)"));
+        assert!(code.contains(
+            r#"router = router.nest("/api", __azap::api::router_with(&state, overrides));"#
+        ));
+        assert!(!code.contains(r#".route("/api/{id}""#));
+        assert!(code.contains(r#".route("/{id}", get(crate::routes::api::get::handler))"#));
+        assert!(code.contains(".fallback(crate::routes::api::_fallback::handler)"));
+    }
+
+    #[test]
+    fn test_generate_catchers() {
+        let mut not_found = route("routes::errors", "404");
+        not_found.method = "catch".to_string();
+        let mut rejection = route("routes::errors", "/");
+        rejection.method = "rejection".to_string();
+        let routes = [route("routes::health", "/"), not_found, rejection];
+
+        let mut router = Router::new();
+        router.generate(&routes, &crate::Config::new(), &Root::default());
+        let code = router.0;
+
+        assert!(code.contains(".on(404, crate::routes::errors::__azap_catch_handler)"));
+        assert!(code.contains(
+            "azap::extract::Rejections(crate::routes::errors::__azap_rejection_handler)"
+        ));
+        assert!(!code.contains(r#".route("/errors"#));
+        assert!(!code.contains("pub mod errors"));
+    }
+
+    #[test]
+    fn test_generate_requirements() {
+        let mut delete = route("routes::users::delete", "/{id}");
+        delete.requirements.roles = vec!["admin".to_string()];
+        delete.requirements.scopes = vec!["users:write".to_string()];
+
+        let mut router = Router::new();
+        router.generate(&[delete], &crate::Config::new(), &Root::default());
+        let code = router.0;
+
+        assert!(code.contains(
+            "azap::authz::Requirements::new(&[\"admin\"], &[\"users:write\"]), \
+            azap::authz::enforce)"
+        ));
+    }
+
+    #[test]
+    fn test_generate_root() {
+        let routes = [route("admin_routes::users::get", "/")];
+        let root = Root::new("admin_routes").state("AdminState");
+
+        let mut router = Router::new();
+        router.generate(&routes, &crate::Config::new(), &root);
+        let code = router.0;
+
+        assert!(code.contains("pub fn register_admin_routes(state: AdminState) -> azap::Router"));
+        assert!(code.contains(r#".route("/users", get(crate::admin_routes::users::get::handler))"#));
+    }
+
+    #[test]
+    fn test_generate_guard_overrides() -> anyhow::Result<()> {
+        let attr: syn::Attribute = syn::parse_quote!(
+            #[guards(::tower_http::trace::TraceLayer::new_for_http())]
+        );
+        let mut users = route("routes::users::get", "/");
+        users.guards = crate::gaurds::parser::Guard::extract_from_attr(
+            &attr,
+            &crate::gaurds::parser::GuardStore::new(),
+        )?;
+
+        let mut router = Router::new();
+        router.generate(&[users], &crate::Config::new(), &Root::default());
+        let code = router.0;
+
+        // The production router doesn't go through the overrides
+        let (register, with_overrides) = code.split_once("azap::__testing! {").unwrap();
+        assert!(register.starts_with("// Auto-generated"));
+        assert!(register.contains("    let group = group.layer(:: tower_http"));
+        assert!(!register.contains("overrides"));
+
+        assert!(with_overrides.contains("    pub fn register_routes_with("));
+        assert!(with_overrides.contains(
+            r#"let group = overrides.apply("tower_http::trace::TraceLayer::new_for_http", group, |group| group.layer(:: tower_http"#
+        ));
+        assert!(with_overrides.contains("overrides.finish();"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_generate_trait_guards() -> anyhow::Result<()> {
+        let store = crate::gaurds::parser::GuardStore::from_source(&[(
+            "roles.rs",
+            "#[register_guard]\nimpl Guard<AppState> for RequireRole {}\n\
+            #[register_guard]\npub const ADMIN: RequireRole = RequireRole(\"admin\");\n\
+            #[register_guard(guard_type = \"authenticate\")]\n\
+            pub async fn user(headers: HeaderMap) -> Authenticated<User> {}\n",
+        )])?;
+
+        let attr: syn::Attribute =
+            syn::parse_quote!(#[guards(RequireRole, roles::ADMIN, roles::user)]);
+        let mut users = route("routes::users::get", "/");
+        users.guards = crate::gaurds::parser::Guard::extract_from_attr(&attr, &store)?;
+
+        let mut router = Router::new();
+        router.generate(&[users], &crate::Config::new(), &Root::default());
+        let code = router.0;
+
+        assert!(code.contains(
+            "azap::guard::from_state::<crate::guards::roles::RequireRole, _>(state.clone())"
+        ));
+        assert!(code.contains("azap::guard::layer(crate::guards::roles::ADMIN, state.clone())"));
+        assert!(code.contains(
+            "azap::guard::layer(azap::guarded::authenticate(crate::guards::roles::user), \
+            state.clone())"
+        ));
+        Ok(())
     }
 }
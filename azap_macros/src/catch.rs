@@ -0,0 +1,98 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, ItemFn, LitInt};
+
+/// `#[catch(404)]` marks a handler that replaces empty responses with that status.
+///
+/// Next to the handler, this emits a `__azap_catch_<name>` adapter with the
+/// `azap::catch::Catcher` signature, which is what `azap_codegen` registers.
+pub(crate) fn catch_macro(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let input_fn = parse_macro_input!(input as ItemFn);
+    let status = parse_macro_input!(attr as LitInt);
+
+    let fn_name = &input_fn.sig.ident;
+    let fn_sig = &input_fn.sig;
+    let help = format!(
+        "#[catch({})]\n\
+        pub async fn {}(caught: Caught) -> impl IntoResponse {{ ... }}",
+        status, fn_name
+    );
+
+    if !status
+        .base10_parse::<u16>()
+        .is_ok_and(|status| (400..=599).contains(&status))
+    {
+        return syn::Error::new_spanned(
+            &status,
+            format!(
+                "'{}' isn't an error status.\n\
+                Help: Catch a 4xx or 5xx status:\n\
+                #[catch(404)]\n\
+                pub async fn {}(caught: Caught) -> impl IntoResponse {{ ... }}",
+                status, fn_name
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if fn_sig.asyncness.is_none() {
+        return syn::Error::new_spanned(
+            fn_sig.fn_token,
+            format!(
+                "Catcher '{}' must be async.\n\
+                Help: Add 'async' keyword before 'fn':\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if !matches!(input_fn.vis, syn::Visibility::Public(_)) {
+        return syn::Error::new_spanned(
+            fn_sig.fn_token,
+            format!(
+                "Catcher '{}' must be public.\n\
+                Help: Add 'pub' keyword:\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let call = match fn_sig.inputs.len() {
+        0 => quote! { #fn_name().await },
+        1 => quote! { #fn_name(caught).await },
+        _ => {
+            return syn::Error::new_spanned(
+                &fn_sig.inputs,
+                format!(
+                    "Catcher '{}' takes at most one argument, the caught request.\n\
+                    Help:\n{}",
+                    fn_name, help
+                ),
+            )
+            .to_compile_error()
+            .into();
+        }
+    };
+
+    let adapter = format_ident!("__azap_catch_{}", fn_name);
+
+    quote! {
+        #input_fn
+
+        #[doc(hidden)]
+        #[allow(unused_variables)]
+        pub fn #adapter(
+            caught: azap::catch::Caught,
+        ) -> ::std::pin::Pin<
+            ::std::boxed::Box<dyn ::std::future::Future<Output = azap::Response> + Send>,
+        > {
+            ::std::boxed::Box::pin(async move { azap::IntoResponse::into_response(#call) })
+        }
+    }
+    .into()
+}
@@ -0,0 +1,67 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, ItemFn};
+
+use crate::route::handler_checks;
+
+/// `#[fallback]` marks the handler for unmatched paths under the file's directory.
+pub(crate) fn fallback_macro(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let input_fn = parse_macro_input!(input as ItemFn);
+
+    let fn_name = &input_fn.sig.ident;
+    let fn_sig = &input_fn.sig;
+
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!(
+                "#[fallback] takes no arguments, it applies to its directory.\n\
+                Help: Move '{}' into the directory it should handle:\n\
+                #[fallback]\n\
+                pub async fn {}(...) {{ ... }}",
+                fn_name, fn_name
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if fn_sig.asyncness.is_none() {
+        return syn::Error::new_spanned(
+            fn_sig.fn_token,
+            format!(
+                "Fallback handler '{}' must be async.\n\
+                Help: Add 'async' keyword before 'fn':\n\
+                #[fallback]\n\
+                pub async fn {}(...) {{ ... }}",
+                fn_name, fn_name
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if !matches!(input_fn.vis, syn::Visibility::Public(_)) {
+        return syn::Error::new_spanned(
+            fn_sig.fn_token,
+            format!(
+                "Fallback handler '{}' must be public.\n\
+                Help: Add 'pub' keyword:\n\
+                #[fallback]\n\
+                pub async fn {}(...) {{ ... }}",
+                fn_name, fn_name
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let handler_checks = handler_checks(fn_sig);
+
+    quote! {
+        #input_fn
+
+        #handler_checks
+    }
+    .into()
+}
@@ -1,2 +1,3 @@
 pub(crate) mod guard;
 pub(crate) mod register;
+pub(crate) mod registry;
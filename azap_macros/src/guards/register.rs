@@ -1,83 +1,250 @@
 use proc_macro::TokenStream;
-use quote::quote;
+use quote::{quote, quote_spanned};
+use syn::spanned::Spanned;
 use syn::{
     parse::{Parse, ParseStream},
-    parse_macro_input, Ident, ItemFn, LitStr, Token,
+    parse_macro_input, FnArg, Ident, Item, ItemFn, LitStr, PatType, ReturnType, Signature, Token,
+    Type,
 };
 
+use azap_syntax::types::generic_arg;
+
 #[derive(Debug, PartialEq, Clone)]
-enum GuardType {
+pub(crate) enum GuardType {
     FromFn,
     FromFnWithState,
+    /// An `async fn` taking extractors and returning `Authenticated<T>`.
+    Authenticate,
     Layer,
 }
 
 impl GuardType {
-    fn from_str(s: &str) -> syn::Result<Self> {
+    pub(crate) fn from_str(s: &str) -> syn::Result<Self> {
         match s {
             "fn" => Ok(GuardType::FromFn),
             "fn_with_state" => Ok(GuardType::FromFnWithState),
+            "authenticate" => Ok(GuardType::Authenticate),
             "layer" => Ok(GuardType::Layer),
             _ => Err(syn::Error::new(
                 proc_macro2::Span::call_site(),
                 format!(
-                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', or 'layer'",
+                    "Unknown guard type: '{}'. Expected 'fn', 'fn_with_state', 'authenticate' or 'layer'",
                     s
                 ),
             )),
         }
     }
 
-    fn to_string(&self) -> String {
+    fn as_str(&self) -> &'static str {
         match self {
-            Self::FromFn => "fn".to_owned(),
-            Self::FromFnWithState => "fn_with_state".to_owned(),
-            Self::Layer => "layer".to_owned(),
+            Self::FromFn => "fn",
+            Self::FromFnWithState => "fn_with_state",
+            Self::Authenticate => "authenticate",
+            Self::Layer => "layer",
         }
     }
 }
 
 #[derive(Debug, PartialEq, Clone)]
-struct RegisterGuardArgs {
-    guard_type: GuardType,
+pub(crate) struct RegisterGuardArgs {
+    /// Required for function guards, trait guards have none.
+    pub(crate) guard_type: Option<GuardType>,
+    /// Types the guard stores as `azap::Guarded<T>` for handlers to extract.
+    pub(crate) provides: Vec<Type>,
 }
 
 impl Parse for RegisterGuardArgs {
     fn parse(input: ParseStream) -> syn::Result<Self> {
-        let key: Ident = input.parse()?;
+        let mut guard_type = None;
+        let mut provides = Vec::new();
 
-        if key != "guard_type" {
-            return Err(syn::Error::new_spanned(key, "Expected 'guard_type'"));
-        };
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            let _: Token![=] = input.parse()?;
 
-        let _: Token![=] = input.parse()?;
+            if key == "guard_type" {
+                let value: LitStr = input.parse()?;
+                guard_type = Some(GuardType::from_str(&value.value())?);
+            } else if key == "provides" {
+                provides.push(input.parse()?);
+            } else {
+                return Err(syn::Error::new_spanned(
+                    key,
+                    "Expected 'guard_type' or 'provides'.\n\
+                    Help: List the types the guard attaches for 'azap::Guarded<T>':\n\
+                    #[register_guard(guard_type = \"fn\", provides = User)]",
+                ));
+            }
 
-        let value: LitStr = input.parse()?;
+            if !input.is_empty() {
+                let _: Token![,] = input.parse()?;
+            }
+        }
 
-        let guard_type = GuardType::from_str(&value.value())?;
+        Ok(RegisterGuardArgs {
+            guard_type,
+            provides,
+        })
+    }
+}
 
-        Ok(RegisterGuardArgs { guard_type })
+/// `Guarded<T>` extracts a clone of what the guard stored in the request extensions.
+pub(crate) fn provides_checks(provides: &[Type]) -> proc_macro2::TokenStream {
+    if provides.is_empty() {
+        return quote! {};
+    }
+
+    let checks = provides.iter().map(|ty| {
+        quote_spanned! {ty.span()=>
+            __azap_check_provided::<#ty>();
+        }
+    });
+
+    quote! {
+        const _: () = {
+            #[allow(dead_code)]
+            fn __azap_check_provided<T: Clone + Send + Sync + 'static>() {}
+
+            #[allow(dead_code)]
+            fn __azap_check() {
+                #(#checks)*
+            }
+        };
     }
 }
 
 pub(crate) fn register_guard_macro(attr: TokenStream, item: TokenStream) -> TokenStream {
-    let input_fn = parse_macro_input!(item as ItemFn);
     let parsed_attr = parse_macro_input!(attr as RegisterGuardArgs);
 
-    let guard_type = parsed_attr.guard_type.to_string();
+    let result = match parse_macro_input!(item as Item) {
+        Item::Fn(input_fn) => return register_fn_guard(parsed_attr, input_fn),
+        Item::Const(item) => trait_guard(&parsed_attr, &item.ident, &item.vis, &item.ty)
+            .map(|()| quote! { #item }),
+        Item::Impl(item) => impl_guard(&parsed_attr, &item).map(|()| quote! { #item }),
+        item => Err(syn::Error::new_spanned(
+            item,
+            "#[register_guard] goes on a guard function, a guard const or an 'impl Guard<S>' block.",
+        )),
+    };
+
+    match result {
+        Ok(item) => {
+            let checks = provides_checks(&parsed_attr.provides);
+            quote! { #item #checks }.into()
+        }
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+/// A `pub const` holding a configured `azap::guard::Guard`.
+fn trait_guard(
+    args: &RegisterGuardArgs,
+    name: &Ident,
+    vis: &syn::Visibility,
+    ty: &Type,
+) -> syn::Result<()> {
+    if args.guard_type.is_some() {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!(
+                "'guard_type' only applies to function guards, '{}' is a trait guard.\n\
+                Help: Remove it:\n\
+                #[register_guard]\n\
+                pub const {}: {} = ...;",
+                name,
+                name,
+                quote!(#ty)
+            ),
+        ));
+    }
+
+    if !matches!(vis, syn::Visibility::Public(_)) {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!(
+                "Guard '{}' must be public.\n\
+                Help: Add 'pub' keyword:\n\
+                #[register_guard]\n\
+                pub const {}: {} = ...;",
+                name,
+                name,
+                quote!(#ty)
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// An `impl Guard<S> for T` block, built from the router state with `FromRef<S>`.
+fn impl_guard(args: &RegisterGuardArgs, item: &syn::ItemImpl) -> syn::Result<()> {
+    let is_guard = item
+        .trait_
+        .as_ref()
+        .and_then(|(_, path, _)| path.segments.last())
+        .is_some_and(|segment| segment.ident == "Guard");
+
+    if !is_guard {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "#[register_guard] on an impl block needs a trait implementation.\n\
+            Help: Implement 'azap::guard::Guard':\n\
+            #[register_guard]\n\
+            impl Guard<AppState> for RequireRole { ... }",
+        ));
+    }
+
+    if args.guard_type.is_some() {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "'guard_type' only applies to function guards.\n\
+            Help: Remove it:\n\
+            #[register_guard]\n\
+            impl Guard<AppState> for RequireRole { ... }",
+        ));
+    }
+
+    if !matches!(item.self_ty.as_ref(), Type::Path(_)) {
+        return Err(syn::Error::new_spanned(
+            &item.self_ty,
+            "Trait guards must be named types, so #[guards(...)] can refer to them.",
+        ));
+    }
+
+    Ok(())
+}
+
+fn register_fn_guard(parsed_attr: RegisterGuardArgs, input_fn: ItemFn) -> TokenStream {
+    let Some(parsed_guard_type) = parsed_attr.guard_type.clone() else {
+        return syn::Error::new_spanned(
+            &input_fn.sig.ident,
+            format!(
+                "Guard '{}' needs a 'guard_type'.\n\
+                Help: Say how azap wraps the function:\n\
+                #[register_guard(guard_type = \"fn\")]",
+                input_fn.sig.ident
+            ),
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    let guard_type = parsed_guard_type.as_str();
     let fn_name = &input_fn.sig.ident;
     let fn_vis = &input_fn.vis;
     let fn_sig = &input_fn.sig;
     let fn_block = &input_fn.block;
     let fn_attrs = &input_fn.attrs;
 
-    if fn_sig.asyncness.is_none() {
+    let is_layer = parsed_guard_type == GuardType::Layer;
+
+    if !is_layer && fn_sig.asyncness.is_none() {
         return syn::Error::new_spanned(
-            &fn_sig.fn_token,
+            fn_sig.fn_token,
             format!(
-                "Route handler '{}' must be async.\n\
+                "Guard '{}' must be async.\n\
                 Help: Add 'async' keyword before 'fn':\n\
-                #[register_guard(\"guard_type = {}\")]\n\
+                #[register_guard(guard_type = \"{}\")]\n\
                 pub async fn {}(...) {{ ... }}",
                 fn_name, guard_type, fn_name
             ),
@@ -88,19 +255,33 @@ pub(crate) fn register_guard_macro(attr: TokenStream, item: TokenStream) -> Toke
 
     if !matches!(fn_vis, syn::Visibility::Public(_)) {
         return syn::Error::new_spanned(
-            &fn_sig.fn_token,
+            fn_sig.fn_token,
             format!(
-                "Route handler '{}' must be public.\n\
+                "Guard '{}' must be public.\n\
                 Help: Add 'pub' keyword:\n\
-                #[register_guard(\"guard_type = {}\")]\n\
-                pub async fn {}(...) {{ ... }}",
-                fn_name, guard_type, fn_name
+                #[register_guard(guard_type = \"{}\")]\n\
+                pub {}fn {}(...) {{ ... }}",
+                fn_name,
+                guard_type,
+                if is_layer { "" } else { "async " },
+                fn_name
             ),
         )
         .to_compile_error()
         .into();
     }
 
+    if let Err(err) = validate_signature(&parsed_guard_type, fn_sig) {
+        return err.to_compile_error().into();
+    }
+
+    // An `authenticate` guard provides what it returns
+    let mut provides = parsed_attr.provides;
+    if let Some(ty) = authenticated_type(&fn_sig.output) {
+        provides.push(ty.clone());
+    }
+    let provides_checks = provides_checks(&provides);
+
     let expand = quote! {
        // guard_type = #guard_type
         #(#fn_attrs)*
@@ -108,8 +289,428 @@ pub(crate) fn register_guard_macro(attr: TokenStream, item: TokenStream) -> Toke
             #fn_block
         }
 
-
+        #provides_checks
     };
 
     expand.into()
 }
+
+/// Checks the guard signature against what `azap_codegen` will generate for its type:
+///
+/// - `fn`: `async fn(Request, Next) -> impl IntoResponse`
+/// - `fn_with_state`: `async fn(State<S>, Request, Next) -> impl IntoResponse`
+/// - `authenticate`: `async fn(extractors...) -> Result<Authenticated<T>, impl IntoResponse>`
+/// - `layer`: `fn() -> impl Layer`, called once while building the router
+fn validate_signature(guard_type: &GuardType, sig: &Signature) -> syn::Result<()> {
+    let fn_name = &sig.ident;
+    let inputs: Vec<&PatType> = sig
+        .inputs
+        .iter()
+        .map(|arg| match arg {
+            FnArg::Typed(pat_type) => Ok(pat_type),
+            FnArg::Receiver(receiver) => Err(syn::Error::new_spanned(
+                receiver,
+                format!("Guard '{}' can't take 'self'", fn_name),
+            )),
+        })
+        .collect::<syn::Result<_>>()?;
+
+    if let GuardType::Layer = guard_type {
+        if sig.asyncness.is_some() {
+            return Err(syn::Error::new_spanned(
+                sig.asyncness,
+                format!(
+                    "Layer guard '{}' must not be async.\n\
+                    Help: A layer guard is called once to build the layer:\n\
+                    #[register_guard(guard_type = \"layer\")]\n\
+                    pub fn {}() -> impl Layer<...> {{ ... }}",
+                    fn_name, fn_name
+                ),
+            ));
+        }
+
+        if let Some(arg) = inputs.first() {
+            return Err(syn::Error::new_spanned(
+                arg,
+                format!(
+                    "Layer guard '{}' must not take arguments.\n\
+                    Help: A layer guard is called once to build the layer:\n\
+                    #[register_guard(guard_type = \"layer\")]\n\
+                    pub fn {}() -> impl Layer<...> {{ ... }}",
+                    fn_name, fn_name
+                ),
+            ));
+        }
+
+        return validate_return_type(guard_type, sig);
+    }
+
+    if let GuardType::Authenticate = guard_type {
+        return validate_authenticate(sig, &inputs);
+    }
+
+    let expected = match guard_type {
+        GuardType::FromFnWithState => "State(state): State<AppState>, req: Request, next: Next",
+        _ => "req: Request, next: Next",
+    };
+    let help = |problem: String| {
+        format!(
+            "{}\n\
+            Help: '{}' guards take the request and the next middleware last:\n\
+            #[register_guard(guard_type = \"{}\")]\n\
+            pub async fn {}({}) -> Response {{ ... }}",
+            problem,
+            guard_type.as_str(),
+            guard_type.as_str(),
+            fn_name,
+            expected
+        )
+    };
+
+    if inputs.len() < 2 {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            help(format!(
+                "Guard '{}' must take 'Request' and 'Next' arguments.",
+                fn_name
+            )),
+        ));
+    }
+
+    let request = inputs[inputs.len() - 2];
+    let next = inputs[inputs.len() - 1];
+
+    if !is_type(&request.ty, "Request") {
+        return Err(syn::Error::new_spanned(
+            &request.ty,
+            help(format!(
+                "Guard '{}' must take 'Request' as its second to last argument.",
+                fn_name
+            )),
+        ));
+    }
+
+    if !is_type(&next.ty, "Next") {
+        return Err(syn::Error::new_spanned(
+            &next.ty,
+            help(format!(
+                "Guard '{}' must take 'Next' as its last argument.",
+                fn_name
+            )),
+        ));
+    }
+
+    match guard_type {
+        GuardType::FromFnWithState => {
+            if !is_type(&inputs[0].ty, "State") {
+                return Err(syn::Error::new_spanned(
+                    &inputs[0].ty,
+                    help(format!(
+                        "Guard '{}' is registered as 'fn_with_state' but doesn't take 'State<_>' first.",
+                        fn_name
+                    )),
+                ));
+            }
+        }
+        _ => {
+            if let Some(state) = inputs.iter().find(|arg| is_type(&arg.ty, "State")) {
+                return Err(syn::Error::new_spanned(
+                    &state.ty,
+                    format!(
+                        "Guard '{}' takes 'State<_>' but is registered as 'fn'.\n\
+                        Help: Register it as a stateful guard:\n\
+                        #[register_guard(guard_type = \"fn_with_state\")]",
+                        fn_name
+                    ),
+                ));
+            }
+        }
+    }
+
+    validate_return_type(guard_type, sig)
+}
+
+fn validate_return_type(guard_type: &GuardType, sig: &Signature) -> syn::Result<()> {
+    let returns_unit = match &sig.output {
+        ReturnType::Default => true,
+        ReturnType::Type(_, ty) => {
+            matches!(ty.as_ref(), Type::Tuple(tuple) if tuple.elems.is_empty())
+        }
+    };
+
+    if !returns_unit {
+        return Ok(());
+    }
+
+    let fn_name = &sig.ident;
+    let (expected, signature) = match guard_type {
+        GuardType::Layer => (
+            "a layer",
+            format!("pub fn {}() -> impl Layer<...>", fn_name),
+        ),
+        _ => (
+            "a response",
+            format!(
+                "pub async fn {}(...) -> Result<Response, StatusCode>",
+                fn_name
+            ),
+        ),
+    };
+
+    Err(syn::Error::new_spanned(
+        fn_name,
+        format!(
+            "Guard '{}' must return {}.\n\
+            Help: Add a return type:\n\
+            #[register_guard(guard_type = \"{}\")]\n\
+            {} {{ ... }}",
+            fn_name,
+            expected,
+            guard_type.as_str(),
+            signature
+        ),
+    ))
+}
+
+/// An `authenticate` guard only takes extractors, azap runs the rest of the request.
+fn validate_authenticate(sig: &Signature, inputs: &[&PatType]) -> syn::Result<()> {
+    let fn_name = &sig.ident;
+    let help = |problem: String| {
+        format!(
+            "{}\n\
+            Help: 'authenticate' guards take extractors and return the authenticated value:\n\
+            #[register_guard(guard_type = \"authenticate\")]\n\
+            pub async fn {}(headers: HeaderMap) -> Result<Authenticated<User>, StatusCode> {{ ... }}",
+            problem, fn_name
+        )
+    };
+
+    if let Some(arg) = inputs
+        .iter()
+        .find(|arg| is_type(&arg.ty, "Request") || is_type(&arg.ty, "Next"))
+    {
+        return Err(syn::Error::new_spanned(
+            &arg.ty,
+            help(format!(
+                "Guard '{}' is registered as 'authenticate' and can't take 'Request' or 'Next'.",
+                fn_name
+            )),
+        ));
+    }
+
+    if authenticated_type(&sig.output).is_none() {
+        let span = match &sig.output {
+            ReturnType::Type(_, ty) => quote!(#ty),
+            ReturnType::Default => quote!(#fn_name),
+        };
+        return Err(syn::Error::new_spanned(
+            span,
+            help(format!(
+                "Guard '{}' must return 'Authenticated<T>' or 'Result<Authenticated<T>, E>'.",
+                fn_name
+            )),
+        ));
+    }
+
+    Ok(())
+}
+
+/// The `T` of an `Authenticated<T>` or `Result<Authenticated<T>, E>` return type.
+fn authenticated_type(output: &ReturnType) -> Option<&Type> {
+    let ReturnType::Type(_, ty) = output else {
+        return None;
+    };
+
+    let ty = match is_type(ty, "Result") {
+        true => generic_arg(ty)?,
+        false => ty.as_ref(),
+    };
+
+    match is_type(ty, "Authenticated") {
+        true => generic_arg(ty),
+        false => None,
+    }
+}
+
+/// Matches on the last path segment, so `Request`, `axum::extract::Request` and
+/// `Request<Body>` all count as `Request`.
+fn is_type(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name),
+        Type::Paren(paren) => is_type(&paren.elem, name),
+        Type::Group(group) => is_type(&group.elem, name),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn validate(guard_type: &str, sig: Signature) -> Result<(), String> {
+        let guard_type = GuardType::from_str(guard_type).map_err(|err| err.to_string())?;
+        validate_signature(&guard_type, &sig).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_validate_fn_signature() {
+        assert!(validate(
+            "fn",
+            parse_quote!(async fn auth(req: Request, next: Next) -> Response)
+        )
+        .is_ok());
+        assert!(validate(
+            "fn",
+            parse_quote!(async fn auth(headers: HeaderMap, req: axum::extract::Request, next: Next) -> Result<Response, StatusCode>)
+        )
+        .is_ok());
+
+        let err =
+            validate("fn", parse_quote!(async fn auth(req: Request) -> Response)).unwrap_err();
+        assert!(err.starts_with("Guard 'auth' must take 'Request' and 'Next' arguments."));
+        assert!(err.contains(
+            "Help: 'fn' guards take the request and the next middleware last:\n\
+            #[register_guard(guard_type = \"fn\")]\n\
+            pub async fn auth(req: Request, next: Next) -> Response { ... }"
+        ));
+
+        let err = validate(
+            "fn",
+            parse_quote!(async fn auth(next: Next, req: Request) -> Response),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Guard 'auth' must take 'Request' as its second to last argument."));
+
+        let err = validate(
+            "fn",
+            parse_quote!(async fn auth(headers: HeaderMap, next: Next) -> Response),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Guard 'auth' must take 'Request' as its second to last argument."));
+
+        let err = validate(
+            "fn",
+            parse_quote!(async fn auth(headers: HeaderMap, req: Request, state: u32) -> Response),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Guard 'auth' must take 'Next' as its last argument."));
+
+        let err = validate(
+            "fn",
+            parse_quote!(async fn auth(State(state): State<AppState>, req: Request, next: Next) -> Response),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Guard 'auth' takes 'State<_>' but is registered as 'fn'."));
+        assert!(err.contains(
+            "Help: Register it as a stateful guard:\n\
+            #[register_guard(guard_type = \"fn_with_state\")]"
+        ));
+
+        let err = validate(
+            "fn",
+            parse_quote!(async fn auth(&self, req: Request, next: Next) -> Response),
+        )
+        .unwrap_err();
+        assert_eq!(err, "Guard 'auth' can't take 'self'");
+    }
+
+    #[test]
+    fn test_validate_fn_with_state_signature() {
+        assert!(validate(
+            "fn_with_state",
+            parse_quote!(async fn auth(State(state): State<AppState>, req: Request, next: Next) -> Response)
+        )
+        .is_ok());
+
+        let err = validate(
+            "fn_with_state",
+            parse_quote!(async fn auth(headers: HeaderMap, req: Request, next: Next) -> Response),
+        )
+        .unwrap_err();
+        assert!(err.starts_with(
+            "Guard 'auth' is registered as 'fn_with_state' but doesn't take 'State<_>' first."
+        ));
+        assert!(err.contains(
+            "pub async fn auth(State(state): State<AppState>, req: Request, next: Next) -> Response { ... }"
+        ));
+    }
+
+    #[test]
+    fn test_validate_return_type() {
+        let err =
+            validate("fn", parse_quote!(async fn auth(req: Request, next: Next))).unwrap_err();
+        assert!(err.starts_with("Guard 'auth' must return a response."));
+        assert!(err.contains(
+            "Help: Add a return type:\n\
+            #[register_guard(guard_type = \"fn\")]\n\
+            pub async fn auth(...) -> Result<Response, StatusCode> { ... }"
+        ));
+
+        let err = validate(
+            "fn",
+            parse_quote!(async fn auth(req: Request, next: Next) -> ()),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("Guard 'auth' must return a response."));
+
+        let err = validate("layer", parse_quote!(fn cors())).unwrap_err();
+        assert!(err.starts_with("Guard 'cors' must return a layer."));
+        assert!(err.contains("pub fn cors() -> impl Layer<...> { ... }"));
+    }
+
+    #[test]
+    fn test_validate_layer_signature() {
+        assert!(validate("layer", parse_quote!(fn cors() -> CorsLayer)).is_ok());
+
+        let err = validate("layer", parse_quote!(async fn cors() -> CorsLayer)).unwrap_err();
+        assert!(err.starts_with("Layer guard 'cors' must not be async."));
+        assert!(err.contains(
+            "Help: A layer guard is called once to build the layer:\n\
+            #[register_guard(guard_type = \"layer\")]\n\
+            pub fn cors() -> impl Layer<...> { ... }"
+        ));
+
+        let err = validate("layer", parse_quote!(fn cors(origin: &str) -> CorsLayer)).unwrap_err();
+        assert!(err.starts_with("Layer guard 'cors' must not take arguments."));
+    }
+
+    #[test]
+    fn test_validate_authenticate_signature() {
+        assert!(validate(
+            "authenticate",
+            parse_quote!(async fn auth(headers: HeaderMap, State(db): State<Db>) -> Result<Authenticated<User>, StatusCode>)
+        )
+        .is_ok());
+        assert!(validate(
+            "authenticate",
+            parse_quote!(async fn auth() -> azap::Authenticated<User>)
+        )
+        .is_ok());
+
+        let err = validate(
+            "authenticate",
+            parse_quote!(async fn auth(req: Request, next: Next) -> Authenticated<User>),
+        )
+        .unwrap_err();
+        assert!(err.starts_with(
+            "Guard 'auth' is registered as 'authenticate' and can't take 'Request' or 'Next'."
+        ));
+        assert!(err.contains(
+            "pub async fn auth(headers: HeaderMap) -> Result<Authenticated<User>, StatusCode> { ... }"
+        ));
+
+        let err = validate(
+            "authenticate",
+            parse_quote!(async fn auth(headers: HeaderMap) -> Result<User, StatusCode>),
+        )
+        .unwrap_err();
+        assert!(err.starts_with(
+            "Guard 'auth' must return 'Authenticated<T>' or 'Result<Authenticated<T>, E>'."
+        ));
+    }
+}
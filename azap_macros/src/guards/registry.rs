@@ -0,0 +1,67 @@
+use proc_macro::TokenStream;
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    punctuated::Punctuated,
+    LitStr, Path, Token, Type,
+};
+
+use crate::guards::register::{provides_checks, GuardType, RegisterGuardArgs};
+
+/// `path => "guard_type"` or `path => { guard_type = "...", provides = T }`
+struct RegistryEntry {
+    _path: Path,
+    _guard_type: GuardType,
+    provides: Vec<Type>,
+}
+
+impl Parse for RegistryEntry {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: Path = input.parse()?;
+        let _: Token![=>] = input.parse()?;
+
+        if input.peek(syn::token::Brace) {
+            let content;
+            let braces = syn::braced!(content in input);
+            let args: RegisterGuardArgs = content.parse()?;
+            let Some(guard_type) = args.guard_type else {
+                return Err(syn::Error::new(
+                    braces.span.join(),
+                    "Expected 'guard_type'.\n\
+                    Help: azap::register_guards! { path => { guard_type = \"fn\", provides = User } }",
+                ));
+            };
+
+            return Ok(RegistryEntry {
+                _path: path,
+                _guard_type: guard_type,
+                provides: args.provides,
+            });
+        }
+
+        let value: LitStr = input.parse()?;
+
+        let guard_type = GuardType::from_str(&value.value())
+            .map_err(|err| syn::Error::new_spanned(&value, err))?;
+
+        Ok(RegistryEntry {
+            _path: path,
+            _guard_type: guard_type,
+            provides: Vec::new(),
+        })
+    }
+}
+
+/// The registry is only read by `azap_codegen`, so it expands to nothing but the
+/// `provides` checks once its entries are known to be well formed.
+pub(crate) fn register_guards_macro(input: TokenStream) -> TokenStream {
+    let entries =
+        parse_macro_input!(input with Punctuated::<RegistryEntry, Token![,]>::parse_terminated);
+
+    let provides: Vec<Type> = entries
+        .into_iter()
+        .flat_map(|entry| entry.provides)
+        .collect();
+
+    provides_checks(&provides).into()
+}
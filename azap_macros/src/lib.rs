@@ -1,12 +1,28 @@
 use proc_macro::TokenStream;
 
 use crate::{
-    guards::{guard::guards_macro, register::register_guard_macro},
+    catch::catch_macro,
+    fallback::fallback_macro,
+    guards::{
+        guard::guards_macro, register::register_guard_macro, registry::register_guards_macro,
+    },
+    mount::mount_macro,
+    public::public_macro,
+    rejection::rejection_macro,
+    requires::requires_macro,
     route::route_macro,
+    routes::routes_macro,
 };
 
+mod catch;
+mod fallback;
 mod guards;
+mod mount;
+mod public;
+mod rejection;
+mod requires;
 mod route;
+mod routes;
 
 #[proc_macro_attribute]
 pub fn get(attr: TokenStream, input: TokenStream) -> TokenStream {
@@ -33,6 +49,41 @@ pub fn delete(attr: TokenStream, input: TokenStream) -> TokenStream {
     route_macro("delete", attr, input)
 }
 
+#[proc_macro_attribute]
+pub fn mount(attr: TokenStream, input: TokenStream) -> TokenStream {
+    mount_macro("mount", attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn service(attr: TokenStream, input: TokenStream) -> TokenStream {
+    mount_macro("service", attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn catch(attr: TokenStream, input: TokenStream) -> TokenStream {
+    catch_macro(attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn fallback(attr: TokenStream, input: TokenStream) -> TokenStream {
+    fallback_macro(attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn rejection(attr: TokenStream, input: TokenStream) -> TokenStream {
+    rejection_macro(attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn public(attr: TokenStream, input: TokenStream) -> TokenStream {
+    public_macro(attr, input)
+}
+
+#[proc_macro_attribute]
+pub fn requires(attr: TokenStream, input: TokenStream) -> TokenStream {
+    requires_macro(attr, input)
+}
+
 #[proc_macro_attribute]
 pub fn guards(attr: TokenStream, input: TokenStream) -> TokenStream {
     guards_macro(attr, input)
@@ -42,3 +93,13 @@ pub fn guards(attr: TokenStream, input: TokenStream) -> TokenStream {
 pub fn register_guard(attr: TokenStream, input: TokenStream) -> TokenStream {
     register_guard_macro(attr, input)
 }
+
+#[proc_macro]
+pub fn register_guards(input: TokenStream) -> TokenStream {
+    register_guards_macro(input)
+}
+
+#[proc_macro]
+pub fn routes(input: TokenStream) -> TokenStream {
+    routes_macro(input)
+}
@@ -0,0 +1,86 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::{parse_macro_input, ItemFn, LitStr};
+
+/// `#[mount("/legacy")]` on a `fn() -> Router<S>` or `#[service("/static")]` on a
+/// `fn() -> impl Service`. `azap_codegen` nests the returned value under the file's base
+/// path with `nest`/`nest_service`.
+pub(crate) fn mount_macro(kind: &str, attr: TokenStream, input: TokenStream) -> TokenStream {
+    let input_fn = parse_macro_input!(input as ItemFn);
+    let path = parse_macro_input!(attr as LitStr);
+
+    let fn_name = &input_fn.sig.ident;
+    let fn_sig = &input_fn.sig;
+    let returns = match kind {
+        "mount" => "Router<AppState>",
+        _ => "ServeDir",
+    };
+    let help = format!(
+        "#[{}(\"{}\")]\n\
+        pub fn {}() -> {} {{ ... }}",
+        kind,
+        path.value(),
+        fn_name,
+        returns
+    );
+
+    if fn_sig.asyncness.is_some() {
+        return syn::Error::new_spanned(
+            fn_sig.asyncness,
+            format!(
+                "'{}' must not be async, it is called once while building the router.\n\
+                Help: Remove 'async':\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if !matches!(input_fn.vis, syn::Visibility::Public(_)) {
+        return syn::Error::new_spanned(
+            fn_sig.fn_token,
+            format!(
+                "'{}' must be public.\n\
+                Help: Add 'pub' keyword:\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if let Some(arg) = fn_sig.inputs.first() {
+        return syn::Error::new_spanned(
+            arg,
+            format!(
+                "'{}' must not take arguments.\n\
+                Help: Build the value from scratch; the router's state is provided by the app:\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let value = path.value();
+    if !value.starts_with('/')
+        || value == "/"
+        || value.ends_with('/')
+        || value.contains(['?', '*', ':'])
+    {
+        return syn::Error::new_spanned(
+            &path,
+            format!(
+                "'{}' is not a valid {} path.\n\
+                Help: Use a prefix like \"/legacy\" without a trailing slash, wildcards, \
+                constraints or optional segments:\n{}",
+                value, kind, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    quote!(#input_fn).into()
+}
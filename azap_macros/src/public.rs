@@ -0,0 +1,24 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, ItemFn};
+
+/// Marks a route or fallback as intentionally open. It may still have guards that don't
+/// authenticate, e.g. tracing. `azap_codegen` reads it from the source, so the handler is left
+/// untouched.
+pub fn public_macro(attr: TokenStream, item: TokenStream) -> TokenStream {
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::TokenStream::from(attr)
+                .into_iter()
+                .next()
+                .map_or_else(proc_macro2::Span::call_site, |token| token.span()),
+            "#[public] takes no arguments.\nHelp: #[public]",
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let item_clone = item.clone();
+    parse_macro_input!(item_clone as ItemFn);
+
+    item
+}
@@ -0,0 +1,80 @@
+use proc_macro::TokenStream;
+use quote::{format_ident, quote};
+use syn::{parse_macro_input, ItemFn};
+
+/// `#[rejection]` marks the function rendering every `azap::extract::Rejection` of the
+/// route root.
+///
+/// Next to the function, this emits a `__azap_rejection_<name>` adapter with the
+/// `azap::extract::RejectionHandler` signature, which is what `azap_codegen` registers.
+pub(crate) fn rejection_macro(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let input_fn = parse_macro_input!(input as ItemFn);
+
+    let fn_name = &input_fn.sig.ident;
+    let fn_sig = &input_fn.sig;
+    let help = format!(
+        "#[rejection]\n\
+        pub fn {}(rejection: Rejection) -> impl IntoResponse {{ ... }}",
+        fn_name
+    );
+
+    if !attr.is_empty() {
+        return syn::Error::new(
+            proc_macro2::Span::call_site(),
+            format!("#[rejection] takes no arguments.\nHelp:\n{}", help),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if fn_sig.asyncness.is_some() {
+        return syn::Error::new_spanned(
+            fn_sig.asyncness,
+            format!(
+                "Rejection handler '{}' must not be async, it renders the response directly.\n\
+                Help: Remove 'async':\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if !matches!(input_fn.vis, syn::Visibility::Public(_)) {
+        return syn::Error::new_spanned(
+            fn_sig.fn_token,
+            format!(
+                "Rejection handler '{}' must be public.\n\
+                Help: Add 'pub' keyword:\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if fn_sig.inputs.len() != 1 {
+        return syn::Error::new_spanned(
+            &fn_sig.inputs,
+            format!(
+                "Rejection handler '{}' must take the rejection as its only argument.\n\
+                Help:\n{}",
+                fn_name, help
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    let adapter = format_ident!("__azap_rejection_{}", fn_name);
+
+    quote! {
+        #input_fn
+
+        #[doc(hidden)]
+        pub fn #adapter(rejection: azap::extract::Rejection) -> azap::Response {
+            azap::IntoResponse::into_response(#fn_name(rejection))
+        }
+    }
+    .into()
+}
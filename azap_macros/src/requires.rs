@@ -0,0 +1,117 @@
+use proc_macro::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::{parse_macro_input, Attribute, Ident, ItemFn, LitStr, Token};
+
+/// Attribute names whose `RouteMetaData` records `#[requires(...)]`.
+const ROUTE_METHODS: &[&str] = &["get", "post", "put", "patch", "delete"];
+
+/// `#[requires(role = "admin", scope = "users:write")]`, keys may repeat.
+#[derive(Default)]
+pub(crate) struct RequiresArgs {
+    pub roles: Vec<LitStr>,
+    pub scopes: Vec<LitStr>,
+}
+
+impl Parse for RequiresArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut args = Self::default();
+
+        while !input.is_empty() {
+            let key: Ident = input.parse()?;
+            input.parse::<Token![=]>()?;
+            let value: LitStr = input.parse()?;
+
+            match key.to_string().as_str() {
+                "role" => args.roles.push(value),
+                "scope" => args.scopes.push(value),
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &key,
+                        format!(
+                            "Unknown requirement '{}'.\n\
+                            Help: Use 'role' or 'scope':\n\
+                            #[requires(role = \"admin\", scope = \"users:write\")]",
+                            key
+                        ),
+                    ))
+                }
+            }
+
+            if !input.is_empty() {
+                input.parse::<Token![,]>()?;
+            }
+        }
+
+        if args.roles.is_empty() && args.scopes.is_empty() {
+            return Err(input.error(
+                "#[requires] needs at least one role or scope.\n\
+                Help: #[requires(role = \"admin\")]",
+            ));
+        }
+
+        Ok(args)
+    }
+}
+
+/// Removes the `#[requires(...)]` attributes from a route handler and merges them.
+pub(crate) fn take_requires(attrs: &mut Vec<Attribute>) -> syn::Result<RequiresArgs> {
+    let mut requires = RequiresArgs::default();
+
+    for attr in std::mem::take(attrs) {
+        if !is_attr(&attr, &["requires"]) {
+            attrs.push(attr);
+            continue;
+        }
+
+        let args = attr.parse_args::<RequiresArgs>()?;
+        requires.roles.extend(args.roles);
+        requires.scopes.extend(args.scopes);
+    }
+
+    Ok(requires)
+}
+
+fn is_attr(attr: &Attribute, names: &[&str]) -> bool {
+    attr.path()
+        .segments
+        .last()
+        .is_some_and(|segment| names.iter().any(|name| segment.ident == name))
+}
+
+/// Route macros read `#[requires]` themselves when it's below them. Above them it expands
+/// first, so it moves itself below the route attribute.
+pub(crate) fn requires_macro(attr: TokenStream, input: TokenStream) -> TokenStream {
+    let attr = proc_macro2::TokenStream::from(attr);
+    if let Err(err) = syn::parse2::<RequiresArgs>(attr.clone()) {
+        return err.to_compile_error().into();
+    }
+
+    let mut input_fn = parse_macro_input!(input as ItemFn);
+
+    let Some(index) = input_fn
+        .attrs
+        .iter()
+        .position(|attr| is_attr(attr, ROUTE_METHODS))
+    else {
+        return syn::Error::new_spanned(
+            input_fn.sig.fn_token,
+            format!(
+                "#[requires] on '{}' needs a route attribute.\n\
+                Help: Use it on a route handler:\n\
+                #[get(\"/\")]\n\
+                #[requires(role = \"admin\")]\n\
+                pub async fn {}(...) {{ ... }}",
+                input_fn.sig.ident, input_fn.sig.ident
+            ),
+        )
+        .to_compile_error()
+        .into();
+    };
+
+    input_fn
+        .attrs
+        .insert(index + 1, syn::parse_quote!(#[azap::requires(#attr)]));
+
+    quote!(#input_fn).into()
+}
@@ -1,20 +1,100 @@
 use proc_macro::TokenStream;
-use quote::quote;
-use syn::{parse_macro_input, ItemFn, LitStr};
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::spanned::Spanned;
+use syn::{parse_macro_input, parse_quote, FnArg, Ident, ItemFn, LitStr, Token, Type};
+
+use azap_syntax::{
+    is_scalar,
+    types::{generic_arg, type_name},
+};
+
+use crate::requires::{take_requires, RequiresArgs};
+
+/// Extractors that consume the request body and therefore must be the last argument.
+const BODY_EXTRACTORS: &[&str] = &[
+    "Json",
+    "Form",
+    "String",
+    "Bytes",
+    "Body",
+    "Request",
+    "RawForm",
+    "Multipart",
+];
+
+/// Extractors that work with any router state, so handlers without a `State<S>`
+/// argument can still have them checked. Others may need a concrete state.
+const STATELESS_EXTRACTORS: &[&str] = &[
+    "Path",
+    "Query",
+    "Json",
+    "Form",
+    "Guarded",
+    "Extension",
+    "HeaderMap",
+    "Method",
+    "Uri",
+    "Version",
+    "String",
+    "Bytes",
+    "Body",
+    "Request",
+    "RawForm",
+    "RawQuery",
+    "RawPathParams",
+    "MatchedPath",
+    "OriginalUri",
+];
+
+/// `#[get("/path")]`, optionally followed by flags such as `validate`.
+struct RouteArgs {
+    path: LitStr,
+    validate: bool,
+}
+
+impl Parse for RouteArgs {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let path: LitStr = input.parse()?;
+        let mut validate = false;
+
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+
+            let flag: Ident = input.parse()?;
+            if flag != "validate" {
+                return Err(syn::Error::new_spanned(
+                    &flag,
+                    format!(
+                        "Unknown route flag '{}'.\n\
+                        Help: The only flag is 'validate':\n\
+                        #[post(\"{}\", validate)]",
+                        flag,
+                        path.value()
+                    ),
+                ));
+            }
+            validate = true;
+        }
+
+        Ok(Self { path, validate })
+    }
+}
 
 pub(crate) fn route_macro(method: &str, attr: TokenStream, input: TokenStream) -> TokenStream {
-    let input_fn = parse_macro_input!(input as ItemFn);
-    let path = parse_macro_input!(attr as LitStr);
+    let mut input_fn = parse_macro_input!(input as ItemFn);
+    let RouteArgs { path, validate } = parse_macro_input!(attr as RouteArgs);
 
-    let fn_name = &input_fn.sig.ident;
+    let fn_name = input_fn.sig.ident.clone();
     let fn_vis = &input_fn.vis;
     let fn_sig = &input_fn.sig;
-    let fn_block = &input_fn.block;
-    let fn_attrs = &input_fn.attrs;
 
     if fn_sig.asyncness.is_none() {
         return syn::Error::new_spanned(
-            &fn_sig.fn_token,
+            fn_sig.fn_token,
             format!(
                 "Route handler '{}' must be async.\n\
                 Help: Add 'async' keyword before 'fn':\n\
@@ -32,7 +112,7 @@ pub(crate) fn route_macro(method: &str, attr: TokenStream, input: TokenStream) -
 
     if !matches!(fn_vis, syn::Visibility::Public(_)) {
         return syn::Error::new_spanned(
-            &fn_sig.fn_token,
+            fn_sig.fn_token,
             format!(
                 "Route handler '{}' must be public.\n\
                 Help: Add 'pub' keyword:\n\
@@ -48,6 +128,55 @@ pub(crate) fn route_macro(method: &str, attr: TokenStream, input: TokenStream) -
         .into();
     }
 
+    if let Some(segment) = path
+        .value()
+        .split('/')
+        .find(|segment| segment.len() > 1 && (segment.starts_with(':') || segment.starts_with('*')))
+    {
+        return syn::Error::new_spanned(
+            &path,
+            format!(
+                "Route \"{}\" uses '{}', the ':param'/'*wildcard' syntax removed in axum 0.8.\n\
+                Help: Wrap the segment in braces, or run `cargo azap fix` to rewrite all routes:\n\
+                #[{}(\"{}\")]",
+                path.value(),
+                segment,
+                method,
+                path.value()
+                    .split('/')
+                    .map(|segment| match segment.strip_prefix(':') {
+                        Some(param) if !param.is_empty() => format!("{{{}}}", param),
+                        _ if segment.len() > 1 && segment.starts_with('*') => {
+                            format!("{{{}}}", segment)
+                        }
+                        _ => segment.to_string(),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("/")
+            ),
+        )
+        .to_compile_error()
+        .into();
+    }
+
+    if let Err(err) = validate_extractors(method, &path, fn_sig) {
+        return err.to_compile_error().into();
+    }
+
+    if validate && let Err(err) = wrap_validated(method, &path, &mut input_fn.sig) {
+        return err.to_compile_error().into();
+    }
+
+    let RequiresArgs { roles, scopes } = match take_requires(&mut input_fn.attrs) {
+        Ok(requires) => requires,
+        Err(err) => return err.to_compile_error().into(),
+    };
+
+    let fn_sig = &input_fn.sig;
+    let fn_vis = &input_fn.vis;
+    let fn_block = &input_fn.block;
+    let fn_attrs = &input_fn.attrs;
+
     let method_upper = method.to_uppercase();
     let metadata_const = quote::format_ident!(
         "__AZAP_ROUTE_{}_{}",
@@ -55,6 +184,13 @@ pub(crate) fn route_macro(method: &str, attr: TokenStream, input: TokenStream) -
         fn_name.to_string().to_uppercase()
     );
 
+    let handler_checks = handler_checks(fn_sig);
+
+    let constraints = path_params(&path.value())
+        .into_iter()
+        .filter_map(|(name, constraint)| Some((name, constraint?)))
+        .map(|(name, constraint)| quote! { (#name, #constraint) });
+
     let expand = quote! {
         #(#fn_attrs)*
         #fn_vis #fn_sig {
@@ -71,9 +207,392 @@ pub(crate) fn route_macro(method: &str, attr: TokenStream, input: TokenStream) -
             module: module_path!(),
             file: file!(),
             line: line!(),
-            column: column!()
+            column: column!(),
+            constraints: &[#(#constraints),*],
+            roles: &[#(#roles),*],
+            scopes: &[#(#scopes),*],
         };
+
+        #handler_checks
     };
 
     TokenStream::from(expand)
 }
+
+/// Wraps the `Json<T>` argument of a `validate` route in `azap::Valid`, so the body is
+/// checked with `azap::Validate` before the handler runs.
+fn wrap_validated(method: &str, path: &LitStr, sig: &mut syn::Signature) -> syn::Result<()> {
+    let fn_name = sig.ident.clone();
+    let mut wrapped = false;
+
+    for arg in sig.inputs.iter_mut() {
+        let FnArg::Typed(pat_type) = arg else {
+            continue;
+        };
+
+        if type_name(&pat_type.ty).as_deref() != Some("Json") {
+            continue;
+        }
+
+        let (pat, ty) = (&pat_type.pat, &pat_type.ty);
+        *pat_type.pat = parse_quote!(azap::Valid(#pat));
+        *pat_type.ty = parse_quote!(azap::Valid<#ty>);
+        wrapped = true;
+    }
+
+    if !wrapped {
+        return Err(syn::Error::new_spanned(
+            path,
+            format!(
+                "Route handler '{}' is marked 'validate' but has no 'Json<T>' argument.\n\
+                Help: Take the body as 'Json<T>' with 'T: azap::Validate':\n\
+                #[{}(\"{}\", validate)]\n\
+                pub async fn {}(Json(body): Json<T>) {{ ... }}",
+                fn_name,
+                method,
+                path.value(),
+                fn_name
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Checks handler extractors against the route:
+///
+/// - a body extractor (`Json<T>`, `String`, ...) must be the last argument
+/// - a `Path<T>` extractor must match the `{params}` in the route string
+fn validate_extractors(method: &str, path: &LitStr, sig: &syn::Signature) -> syn::Result<()> {
+    let fn_name = &sig.ident;
+    let args: Vec<&Type> = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => Some(pat_type.ty.as_ref()),
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+
+    for (index, ty) in args.iter().enumerate() {
+        let Some(name) = type_name(ty) else {
+            continue;
+        };
+        // `Valid<Json<T>>` consumes the body like `Json<T>` does.
+        let name = match name.as_str() {
+            "Valid" => match generic_arg(ty).and_then(type_name) {
+                Some(inner) => inner,
+                None => continue,
+            },
+            _ => name,
+        };
+
+        if index + 1 < args.len() && BODY_EXTRACTORS.contains(&name.as_str()) {
+            return Err(syn::Error::new_spanned(
+                ty,
+                format!(
+                    "'{}' consumes the request body and must be the last argument of '{}'.\n\
+                    Help: Move it after all other extractors:\n\
+                    #[{}(\"{}\")]\n\
+                    pub async fn {}(..., body: {}) {{ ... }}",
+                    name,
+                    fn_name,
+                    method,
+                    path.value(),
+                    fn_name,
+                    quote!(#ty)
+                        .to_string()
+                        .replace(" < ", "<")
+                        .replace(" >", ">")
+                ),
+            ));
+        }
+    }
+
+    let params: Vec<String> = path_params(&path.value())
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+
+    for ty in &args {
+        if type_name(ty).as_deref() != Some("Path") {
+            continue;
+        }
+
+        let expected = match generic_arg(ty) {
+            Some(Type::Tuple(tuple)) => Some(tuple.elems.len()),
+            Some(inner) if type_name(inner).is_some_and(|n| is_scalar(&n)) => Some(1),
+            // Structs and maps are matched by name, which only codegen can check.
+            _ => None,
+        };
+
+        let mismatch = match expected {
+            Some(count) => count != params.len(),
+            None => params.is_empty(),
+        };
+
+        if mismatch {
+            return Err(syn::Error::new_spanned(
+                ty,
+                format!(
+                    "'{}' extracts {} path parameter(s) but the route \"{}\" has {}{}.\n\
+                    Help: Make the route parameters and the 'Path' extractor agree:\n\
+                    #[{}(\"/{{id}}\")]\n\
+                    pub async fn {}(Path(id): Path<u32>) {{ ... }}",
+                    fn_name,
+                    expected.map_or_else(|| "named".to_string(), |count| count.to_string()),
+                    path.value(),
+                    params.len(),
+                    if params.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" ({})", params.join(", "))
+                    },
+                    method,
+                    fn_name
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Debug-build checks in the spirit of `axum::debug_handler`, spanned on the handler so
+/// errors don't surface at the generated `.route()` call:
+///
+/// - the return type implements `IntoResponse`
+/// - the returned future is `Send`
+/// - with a `State<S>` argument, every argument is an extractor for `S`
+/// - without one, the arguments that don't depend on the state are extractors for any
+///   state, e.g. `Option<Path<T>>`
+pub(crate) fn handler_checks(sig: &syn::Signature) -> proc_macro2::TokenStream {
+    if !sig.generics.params.is_empty() {
+        return quote! {};
+    }
+
+    let arg_types: Vec<&Type> = sig
+        .inputs
+        .iter()
+        .filter_map(|arg| match arg {
+            FnArg::Typed(pat_type) => Some(pat_type.ty.as_ref()),
+            FnArg::Receiver(_) => None,
+        })
+        .collect();
+
+    if arg_types.len() != sig.inputs.len()
+        || arg_types.iter().any(|ty| matches!(ty, Type::ImplTrait(_)))
+    {
+        return quote! {};
+    }
+
+    let fn_name = &sig.ident;
+
+    // Checked through the function item, so `-> impl IntoResponse` doesn't have to be
+    // named.
+    let output_span = match &sig.output {
+        syn::ReturnType::Default => fn_name.span(),
+        syn::ReturnType::Type(_, ty) => ty.span(),
+    };
+    let into_response = quote_spanned! {output_span=>
+        __azap_check_into_response(#fn_name);
+    };
+
+    let future_send = quote_spanned! {fn_name.span()=>
+        __azap_check_future_send(#fn_name);
+    };
+
+    let state = arg_types
+        .iter()
+        .find(|ty| type_name(ty).as_deref() == Some("State"))
+        .and_then(|ty| generic_arg(ty));
+
+    let extractor_checks = |state: proc_macro2::TokenStream, all: bool| {
+        let checks = arg_types.iter().enumerate().map(|(i, ty)| {
+            if !all && !is_stateless(ty) {
+                quote! {}
+            } else if i + 1 == arg_types.len() {
+                quote_spanned! {ty.span()=>
+                    __azap_check_from_request::<#ty, #state, _>();
+                }
+            } else {
+                quote_spanned! {ty.span()=>
+                    __azap_check_from_request_parts::<#ty, #state>();
+                }
+            }
+        });
+        quote! { #(#checks)* }
+    };
+
+    let extractors = match state {
+        Some(state) => extractor_checks(quote! { #state }, true),
+        None => quote! {},
+    };
+    let stateless_extractors = match state {
+        Some(_) => quote! {},
+        None => extractor_checks(quote! { S }, false),
+    };
+
+    quote! {
+        #[cfg(debug_assertions)]
+        const _: () = {
+            #[allow(dead_code)]
+            fn __azap_check_into_response<F, Fut>(_: F)
+            where
+                F: FnOnce(#(#arg_types),*) -> Fut,
+                Fut: ::core::future::Future,
+                Fut::Output: azap::IntoResponse,
+            {
+            }
+
+            #[allow(dead_code)]
+            fn __azap_check_future_send<F, Fut>(_: F)
+            where
+                F: FnOnce(#(#arg_types),*) -> Fut,
+                Fut: ::core::future::Future + Send,
+            {
+            }
+
+            #[allow(dead_code)]
+            fn __azap_check_from_request_parts<T, S>()
+            where
+                T: azap::axum::extract::FromRequestParts<S>,
+                S: Send + Sync,
+            {
+            }
+
+            #[allow(dead_code)]
+            fn __azap_check_from_request<T, S, M>()
+            where
+                T: azap::axum::extract::FromRequest<S, M>,
+                S: Send + Sync,
+            {
+            }
+
+            #[allow(dead_code)]
+            fn __azap_check() {
+                #into_response
+                #future_send
+                #extractors
+            }
+
+            #[allow(dead_code)]
+            fn __azap_check_stateless<S: Send + Sync + 'static>() {
+                #stateless_extractors
+            }
+        };
+    }
+}
+
+/// Whether `ty` extracts the same way whatever the router state, looking through
+/// `Option`, `Result` and `Valid`.
+fn is_stateless(ty: &Type) -> bool {
+    match type_name(ty).as_deref() {
+        Some("Option" | "Result" | "Valid") => generic_arg(ty).is_some_and(is_stateless),
+        Some(name) => STATELESS_EXTRACTORS.contains(&name),
+        None => false,
+    }
+}
+
+/// Returns the `{param}` names of a route path with their optional `:constraint`, e.g.
+/// `[("id", Some("u64")), ("rest", None)]` for `/{id:u64}/{*rest}`. An unclosed `{` is
+/// reported by `azap_codegen`.
+fn path_params(path: &str) -> Vec<(String, Option<String>)> {
+    azap_syntax::params(path)
+        .unwrap_or_default()
+        .iter()
+        .map(|param| {
+            (
+                param.name().to_string(),
+                param.constraint.map(str::to_string),
+            )
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(path: &str, sig: syn::Signature) -> Result<(), String> {
+        let path = LitStr::new(path, proc_macro2::Span::call_site());
+        validate_extractors("get", &path, &sig).map_err(|err| err.to_string())
+    }
+
+    #[test]
+    fn test_validate_extractors() {
+        assert!(validate(
+            "/{id}",
+            parse_quote!(async fn update(Path(id): Path<u32>, Json(body): Json<User>))
+        )
+        .is_ok());
+        assert!(validate(
+            "/{id}/{name}",
+            parse_quote!(async fn show(Path((id, name)): Path<(u32, String)>))
+        )
+        .is_ok());
+        assert!(validate(
+            "/{id}",
+            parse_quote!(async fn show(Path(params): Path<Params>))
+        )
+        .is_ok());
+
+        let err = validate(
+            "/",
+            parse_quote!(async fn create(Json(body): Json<User>, headers: HeaderMap)),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("'Json' consumes the request body and must be the last argument"));
+
+        let err = validate(
+            "/",
+            parse_quote!(async fn create(body: Valid<Json<User>>, headers: HeaderMap)),
+        )
+        .unwrap_err();
+        assert!(err.starts_with("'Json' consumes the request body"));
+
+        let err = validate(
+            "/{id}/{name}",
+            parse_quote!(async fn show(Path(id): Path<u32>)),
+        )
+        .unwrap_err();
+        assert!(err.starts_with(
+            "'show' extracts 1 path parameter(s) but the route \"/{id}/{name}\" has 2 (id, name)."
+        ));
+
+        let err = validate("/", parse_quote!(async fn show(Path(p): Path<Params>))).unwrap_err();
+        assert!(
+            err.starts_with("'show' extracts named path parameter(s) but the route \"/\" has 0.")
+        );
+    }
+
+    #[test]
+    fn test_handler_checks() {
+        let checks = handler_checks(&parse_quote!(
+            async fn show(State(state): State<AppState>, Path(id): Path<u32>) -> impl IntoResponse
+        ))
+        .to_string();
+
+        assert!(checks.contains("__azap_check_into_response (show)"));
+        assert!(checks.contains("__azap_check_future_send (show)"));
+        assert!(checks
+            .contains("__azap_check_from_request_parts :: < State < AppState > , AppState > ()"));
+        assert!(checks.contains("__azap_check_from_request :: < Path < u32 > , AppState , _ > ()"));
+        assert!(!checks.contains("< impl IntoResponse >"));
+
+        // Without a `State`, only extractors that work with any state are checked
+        let checks = handler_checks(&parse_quote!(
+            async fn docs(lang: Option<Path<String>>, user: CurrentUser, body: Json<Doc>)
+        ))
+        .to_string();
+        assert!(checks.contains("fn __azap_check_stateless < S : Send + Sync + 'static > ()"));
+        assert!(checks
+            .contains("__azap_check_from_request_parts :: < Option < Path < String > > , S > ()"));
+        assert!(checks.contains("__azap_check_from_request :: < Json < Doc > , S , _ > ()"));
+        assert!(!checks.contains("CurrentUser , S"));
+
+        // Nothing to name the argument types with
+        assert!(handler_checks(&parse_quote!(async fn show(body: impl Into<String>))).is_empty());
+        assert!(handler_checks(&parse_quote!(async fn show<T>(body: Json<T>))).is_empty());
+    }
+}
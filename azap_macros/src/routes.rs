@@ -0,0 +1,54 @@
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, punctuated::Punctuated, Expr, ExprLit, Lit, MetaNameValue, Token};
+
+/// Per-directory route settings, read by `azap_codegen` from a directory's module file.
+/// Expands to nothing once the arguments are known to be well formed.
+pub(crate) fn routes_macro(input: TokenStream) -> TokenStream {
+    let args =
+        parse_macro_input!(input with Punctuated::<MetaNameValue, Token![,]>::parse_terminated);
+
+    for arg in &args {
+        let Some(key) = arg.path.get_ident() else {
+            return syn::Error::new_spanned(&arg.path, "Expected a setting name")
+                .to_compile_error()
+                .into();
+        };
+
+        match key.to_string().as_str() {
+            "path" | "deprecated" | "sunset" | "link" => {
+                if !matches!(
+                    &arg.value,
+                    Expr::Lit(ExprLit {
+                        lit: Lit::Str(_),
+                        ..
+                    })
+                ) {
+                    return syn::Error::new_spanned(
+                        &arg.value,
+                        format!(
+                            "Expected a string literal.\n\
+                            Help: azap::routes!({} = \"{}\");",
+                            key,
+                            match key.to_string().as_str() {
+                                "deprecated" => "2025-06-30",
+                                _ => "...",
+                            }
+                        ),
+                    )
+                    .to_compile_error()
+                    .into();
+                }
+            }
+            _ => {
+                return syn::Error::new_spanned(
+                    key,
+                    format!("Unknown routes! setting: '{}'. Expected 'path', 'deprecated', 'sunset' or 'link'", key),
+                )
+                .to_compile_error()
+                .into();
+            }
+        }
+    }
+
+    TokenStream::new()
+}
@@ -0,0 +1,156 @@
+//! Route path and type syntax shared by `azap`, `azap_macros` and `azap_codegen`, so the
+//! build-time checks and the runtime agree. Not a public API.
+
+use std::{fmt, ops::Range};
+
+#[cfg(feature = "syn")]
+pub mod types;
+
+/// Types a `{param:type}` constraint may name. Anything else is treated as a regex.
+pub const CONSTRAINT_TYPES: &[&str] = &[
+    "u8", "u16", "u32", "u64", "u128", "usize", "i8", "i16", "i32", "i64", "i128", "isize", "f32",
+    "f64", "bool", "char",
+];
+
+/// Returns `true` if `name` deserializes from exactly one path segment.
+pub fn is_scalar(name: &str) -> bool {
+    CONSTRAINT_TYPES.contains(&name) || matches!(name, "String" | "Uuid")
+}
+
+/// Returns whether `value` parses as the [`CONSTRAINT_TYPES`] entry `ty`, `None` if `ty`
+/// isn't one.
+pub fn parses_as(ty: &str, value: &str) -> Option<bool> {
+    let ok = match ty {
+        "u8" => value.parse::<u8>().is_ok(),
+        "u16" => value.parse::<u16>().is_ok(),
+        "u32" => value.parse::<u32>().is_ok(),
+        "u64" => value.parse::<u64>().is_ok(),
+        "u128" => value.parse::<u128>().is_ok(),
+        "usize" => value.parse::<usize>().is_ok(),
+        "i8" => value.parse::<i8>().is_ok(),
+        "i16" => value.parse::<i16>().is_ok(),
+        "i32" => value.parse::<i32>().is_ok(),
+        "i64" => value.parse::<i64>().is_ok(),
+        "i128" => value.parse::<i128>().is_ok(),
+        "isize" => value.parse::<isize>().is_ok(),
+        "f32" => value.parse::<f32>().is_ok(),
+        "f64" => value.parse::<f64>().is_ok(),
+        "bool" => value.parse::<bool>().is_ok(),
+        "char" => value.parse::<char>().is_ok(),
+        _ => return None,
+    };
+
+    Some(ok)
+}
+
+/// A `{...}` param of a route path.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Param<'a> {
+    /// Byte range of the whole `{...}` in the path.
+    pub span: Range<usize>,
+    /// The name as written, e.g. `*rest` or `lang?`.
+    pub raw_name: &'a str,
+    /// The text after the first `:`, e.g. `u64` or `[a-z]{2}`.
+    pub constraint: Option<&'a str>,
+}
+
+impl<'a> Param<'a> {
+    /// The name without the wildcard `*` and optional `?` markers.
+    pub fn name(&self) -> &'a str {
+        self.raw_name.trim_start_matches('*').trim_end_matches('?')
+    }
+
+    pub fn is_optional(&self) -> bool {
+        self.raw_name.ends_with('?')
+    }
+}
+
+/// A `{` without its closing `}`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UnclosedBrace;
+
+impl fmt::Display for UnclosedBrace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("unclosed `{` in route path")
+    }
+}
+
+impl std::error::Error for UnclosedBrace {}
+
+/// Returns the `{...}` params of a route path, e.g. `id` and `rest` for `/{id:u64}/{*rest}`.
+pub fn params(path: &str) -> Result<Vec<Param<'_>>, UnclosedBrace> {
+    let mut params = Vec::new();
+    let mut chars = path.char_indices();
+
+    while let Some((start, c)) = chars.next() {
+        if c != '{' {
+            continue;
+        }
+
+        // Regex constraints may contain braces themselves, e.g. `{code:[a-z]{2}}`.
+        let mut depth = 1;
+        let mut end = None;
+
+        for (index, c) in chars.by_ref() {
+            match c {
+                '{' => depth += 1,
+                '}' => depth -= 1,
+                _ => {}
+            }
+
+            if depth == 0 {
+                end = Some(index);
+                break;
+            }
+        }
+
+        let end = end.ok_or(UnclosedBrace)?;
+        let param = &path[start + 1..end];
+        let (raw_name, constraint) = match param.split_once(':') {
+            Some((name, constraint)) => (name, Some(constraint)),
+            None => (param, None),
+        };
+
+        params.push(Param {
+            span: start..end + 1,
+            raw_name,
+            constraint,
+        });
+    }
+
+    Ok(params)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_params() {
+        let path = "/users/{id:u64}/{code:[a-z]{2}}/{lang?}/{*rest}";
+        let params = params(path).unwrap();
+
+        assert_eq!(
+            params.iter().map(Param::name).collect::<Vec<_>>(),
+            ["id", "code", "lang", "rest"]
+        );
+        assert_eq!(params[1].constraint, Some("[a-z]{2}"));
+        assert_eq!(&path[params[1].span.clone()], "{code:[a-z]{2}}");
+        assert!(params[2].is_optional());
+        assert_eq!(params[3].raw_name, "*rest");
+
+        assert_eq!(super::params("/{id"), Err(UnclosedBrace));
+        assert!(super::params("/users").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_parses_as() {
+        for ty in CONSTRAINT_TYPES {
+            assert!(parses_as(ty, "1").is_some(), "`{}` has no parser", ty);
+        }
+
+        assert_eq!(parses_as("u8", "256"), Some(false));
+        assert_eq!(parses_as("String", "x"), None);
+        assert!(is_scalar("Uuid") && is_scalar("u64") && !is_scalar("Params"));
+    }
+}
@@ -0,0 +1,38 @@
+//! Name-based helpers for `syn` types. Macros and codegen only see tokens, so extractors
+//! are recognized by their last path segment.
+
+use syn::{GenericArgument, PathArguments, Type};
+
+/// The last path segment of a type, e.g. `Json` for `axum::Json<User>`. `&str` is
+/// reported as `String`.
+pub fn type_name(ty: &Type) -> Option<String> {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string()),
+        Type::Reference(reference) if matches!(reference.elem.as_ref(), Type::Path(p) if p.path.is_ident("str")) => {
+            Some("String".to_string())
+        }
+        Type::Paren(paren) => type_name(&paren.elem),
+        Type::Group(group) => type_name(&group.elem),
+        _ => None,
+    }
+}
+
+/// The first generic argument of a type, e.g. `(u32, String)` for `Path<(u32, String)>`.
+pub fn generic_arg(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+
+    let PathArguments::AngleBracketed(args) = &type_path.path.segments.last()?.arguments else {
+        return None;
+    };
+
+    args.args.iter().find_map(|arg| match arg {
+        GenericArgument::Type(ty) => Some(ty),
+        _ => None,
+    })
+}
@@ -0,0 +1,57 @@
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+
+const USAGE: &str = "\
+Usage: cargo azap <COMMAND>
+
+Commands:
+  fix [CRATE_DIR]  Rewrite axum 0.7 `:param`/`*wildcard` route paths in every route
+                   root under src to axum 0.8 `{param}`/`{*wildcard}` syntax";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("error: {:#}", err);
+        std::process::exit(1);
+    }
+}
+
+fn run() -> Result<()> {
+    // `cargo azap fix` invokes us as `cargo-azap azap fix`.
+    let mut args = std::env::args().skip(1).peekable();
+    if args.peek().is_some_and(|arg| arg == "azap") {
+        args.next();
+    }
+
+    match args.next().as_deref() {
+        Some("fix") => fix(args.next().map(PathBuf::from).unwrap_or_default()),
+        Some("-h" | "--help") | None => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        Some(command) => bail!("unknown command `{}`\n\n{}", command, USAGE),
+    }
+}
+
+fn fix(crate_dir: PathBuf) -> Result<()> {
+    // Walks all of src, so roots other than src/routes are migrated too.
+    let src_dir = crate_dir.join("src");
+
+    if !src_dir.exists() {
+        bail!("{} doesn't exist", src_dir.display());
+    }
+
+    let fixes = azap_codegen::fix_routes(&src_dir)?;
+
+    for fix in &fixes {
+        println!(
+            "{}:{}: \"{}\" -> \"{}\"",
+            fix.file.display(),
+            fix.line,
+            fix.old,
+            fix.new
+        );
+    }
+
+    println!("Rewrote {} route path(s)", fixes.len());
+    Ok(())
+}
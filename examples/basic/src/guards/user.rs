@@ -0,0 +1,18 @@
+use azap::{axum::http::HeaderMap, register_guard, Authenticated, StatusCode};
+
+#[derive(Debug, Clone)]
+pub struct User {
+    pub name: String,
+}
+
+#[register_guard(guard_type = "authenticate")]
+pub async fn user(headers: HeaderMap) -> Result<Authenticated<User>, StatusCode> {
+    let name = headers
+        .get("x-user")
+        .and_then(|value| value.to_str().ok())
+        .ok_or(StatusCode::UNAUTHORIZED)?;
+
+    Ok(Authenticated(User {
+        name: name.to_string(),
+    }))
+}
@@ -27,6 +27,7 @@ async fn main() {
     println!("  GET  http://127.0.0.1:3000/health");
     println!("  GET  http://127.0.0.1:3000/users");
     println!("  GET  http://127.0.0.1:3000/users/123");
+    println!("  GET  http://127.0.0.1:3000/users/me (with an x-user header)");
     println!("  POST http://127.0.0.1:3000/users");
 
     azap::serve(listener, app).await.unwrap();
@@ -7,7 +7,20 @@ pub struct CreateUser {
     email: String,
 }
 
-#[post("/")]
+impl Validate for CreateUser {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        ValidationErrors::new()
+            .check(!self.name.trim().is_empty(), "name", "must not be empty")
+            .check(
+                self.email.contains('@'),
+                "email",
+                "must be an email address",
+            )
+            .into_result()
+    }
+}
+
+#[post("/", validate)]
 #[guards(auth, tracing)]
 pub async fn create_user(Json(payload): Json<CreateUser>) -> Json<serde_json::Value> {
     Json(serde_json::json!({
@@ -1,5 +1,7 @@
 use azap::{guards, prelude::*};
 
+use crate::guards::user::User;
+
 #[get("/")]
 #[guards(auth)]
 pub async fn list_users() -> Json<serde_json::Value> {
@@ -11,7 +13,7 @@ pub async fn list_users() -> Json<serde_json::Value> {
     }))
 }
 
-#[get("/{id}")]
+#[get("/{id:u32}")]
 #[guards(tracing)]
 pub async fn get_user(Path(id): Path<u32>) -> Json<serde_json::Value> {
     Json(serde_json::json!({
@@ -19,3 +21,9 @@ pub async fn get_user(Path(id): Path<u32>) -> Json<serde_json::Value> {
         "name": "User Name"
     }))
 }
+
+#[get("/me")]
+#[guards(user)]
+pub async fn me(Guarded(user): Guarded<User>) -> String {
+    format!("Hello, {}", user.name)
+}